@@ -2,13 +2,16 @@ use darling::{ast::NestedMeta, Error, FromDeriveInput, FromMeta};
 use proc_macro::TokenStream;
 use quote::quote;
 
-/// `#[fetch(name = "...", priority = N, colour = "field")]`
+/// `#[fetch(name = "...", priority = N, colour = "field", config, icon = "...", ascii_icon = "...")]`
 ///
 /// `name`     — display label; defaults to struct name.
 /// `priority` — when present, registers the module with inventory so Machine
 ///              picks it up automatically. Structs without priority (e.g. helper
 ///              types that derive Fetch for other reasons) are left unregistered.
 /// `colour`   — JSON field name to read the terminal colour from (OsInfo only).
+/// `config`   — the module is constructed with `new(&Config)` instead of `new()`.
+/// `icon`     — Nerd Font glyph shown with the label in `--icons` mode.
+/// `ascii_icon` — plain-ASCII stand-in for `icon` in `--icons ascii` mode.
 #[derive(FromDeriveInput, Clone)]
 #[darling(attributes(fetch), supports(struct_named, struct_newtype))]
 struct DeriveMacroArgs {
@@ -16,9 +19,13 @@ struct DeriveMacroArgs {
     name: Option<String>,
     priority: Option<u32>,
     colour: Option<String>,
+    #[darling(default)]
+    config: bool,
+    icon: Option<String>,
+    ascii_icon: Option<String>,
 }
 
-/// `#[register_module(name = "...", priority = N, colour = "field")]`
+/// `#[register_module(name = "...", priority = N, colour = "field", config, icon = "...", ascii_icon = "...")]`
 ///
 /// Generates `DynModule` + `inventory::submit!` for structs that implement
 /// `Fetch` manually (e.g. because they need a custom `as_fetchlines`).
@@ -30,6 +37,10 @@ struct RegisterModuleArgs {
     name: Option<String>,
     priority: u32,
     colour: Option<String>,
+    #[darling(default)]
+    config: bool,
+    icon: Option<String>,
+    ascii_icon: Option<String>,
 }
 
 #[proc_macro_derive(Fetch, attributes(fetch))]
@@ -65,14 +76,15 @@ pub fn register_module(args: TokenStream, input: TokenStream) -> TokenStream {
     let priority = reg_args.priority;
 
     let colour_override = build_colour_override(reg_args.colour);
+    let load_module = build_load_module(reg_args.config);
+    let icon = build_opt_str(reg_args.icon.as_deref());
+    let ascii_icon = build_opt_str(reg_args.ascii_icon.as_deref());
 
     quote! {
         #item
 
         impl crate::fetch::DynModule for #struct_ident {
-            fn load_module() -> ::core::option::Option<Self> {
-                Self::new().ok().flatten()
-            }
+            #load_module
             #colour_override
         }
 
@@ -83,6 +95,8 @@ pub fn register_module(args: TokenStream, input: TokenStream) -> TokenStream {
                 load: <#struct_ident as crate::fetch::DynModule>::load_dyn,
                 display: <#struct_ident as crate::fetch::DynModule>::display_dyn,
                 colour: <#struct_ident as crate::fetch::DynModule>::colour_dyn,
+                icon: #icon,
+                ascii_icon: #ascii_icon,
             }
         }
     }
@@ -102,6 +116,9 @@ fn impl_fetch_macro(ast: &syn::DeriveInput) -> TokenStream {
         name,
         priority,
         colour,
+        config,
+        icon,
+        ascii_icon,
     } = args;
 
     let name_string = name.as_deref().unwrap_or(&struct_ident_str).to_string();
@@ -122,14 +139,15 @@ fn impl_fetch_macro(ast: &syn::DeriveInput) -> TokenStream {
     };
 
     let colour_override = build_colour_override(colour);
+    let load_module = build_load_module(config);
+    let icon = build_opt_str(icon.as_deref());
+    let ascii_icon = build_opt_str(ascii_icon.as_deref());
 
     quote! {
         #fetch_impl
 
         impl crate::fetch::DynModule for #struct_ident {
-            fn load_module() -> ::core::option::Option<Self> {
-                Self::new().ok().flatten()
-            }
+            #load_module
             #colour_override
         }
 
@@ -140,12 +158,30 @@ fn impl_fetch_macro(ast: &syn::DeriveInput) -> TokenStream {
                 load: <#struct_ident as crate::fetch::DynModule>::load_dyn,
                 display: <#struct_ident as crate::fetch::DynModule>::display_dyn,
                 colour: <#struct_ident as crate::fetch::DynModule>::colour_dyn,
+                icon: #icon,
+                ascii_icon: #ascii_icon,
             }
         }
     }
     .into()
 }
 
+fn build_load_module(config: bool) -> proc_macro2::TokenStream {
+    if config {
+        quote! {
+            fn load_module(config: &crate::config::Config) -> ::core::option::Option<Self> {
+                Self::new(config).ok().flatten()
+            }
+        }
+    } else {
+        quote! {
+            fn load_module(_config: &crate::config::Config) -> ::core::option::Option<Self> {
+                Self::new().ok().flatten()
+            }
+        }
+    }
+}
+
 fn build_colour_override(field: Option<String>) -> proc_macro2::TokenStream {
     match field {
         Some(f) => quote! {
@@ -156,3 +192,10 @@ fn build_colour_override(field: Option<String>) -> proc_macro2::TokenStream {
         None => quote! {},
     }
 }
+
+fn build_opt_str(value: Option<&str>) -> proc_macro2::TokenStream {
+    match value {
+        Some(v) => quote! { ::core::option::Option::Some(#v) },
+        None => quote! { ::core::option::Option::None },
+    }
+}
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 
 /// A simple system fetcher
 #[derive(Parser, Debug)]
@@ -29,4 +29,333 @@ pub struct Args {
     /// Hide the colour blocks at the bottom
     #[arg(long)]
     pub no_colour_blocks: bool,
+
+    /// Palette indices shown as colour blocks, e.g. 0-7, 0-15 or 0-255 [default: 0-15]
+    #[arg(long, value_name = "FIRST-LAST", value_parser = parse_block_range)]
+    pub block_range: Option<(u8, u8)>,
+
+    /// Colour blocks per row [default: 8]
+    #[arg(long, value_name = "COUNT")]
+    pub block_row: Option<usize>,
+
+    /// How each colour block is drawn
+    #[arg(long, value_enum, default_value_t = BlockGlyph::Space, value_name = "GLYPH")]
+    pub block_glyph: BlockGlyph,
+
+    /// Columns per colour block [default: 3]
+    #[arg(long, value_name = "COLUMNS")]
+    pub block_width: Option<usize>,
+
+    /// Where the colour blocks start
+    #[arg(long, value_enum, default_value_t = BlockAlign::Content, value_name = "ALIGN")]
+    pub block_align: BlockAlign,
+
+    /// Read package databases from this root instead of / (e.g. a mounted image).
+    /// Per-user packages are those of $USER, whose home is taken from the
+    /// root's /etc/passwd
+    #[arg(long, value_name = "PATH")]
+    pub sysroot: Option<PathBuf>,
+
+    /// Details to leave out of the network module; comma-separated or repeated
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "DETAIL")]
+    pub network_hide: Vec<NetworkHide>,
+
+    /// How much of the hardware sensors to show
+    #[arg(long, value_enum, default_value_t = SensorView::Summary, value_name = "VIEW")]
+    pub sensors: SensorView,
+
+    /// Per-core load at which the load module turns yellow [default: 0.7]
+    #[arg(long, value_name = "RATIO")]
+    pub load_warning: Option<f64>,
+
+    /// Per-core load at which the load module turns red [default: 1.0]
+    #[arg(long, value_name = "RATIO")]
+    pub load_critical: Option<f64>,
+
+    /// Treat this DMI value as unset, in addition to the built-in placeholders; repeatable
+    #[arg(long, value_name = "VALUE")]
+    pub dmi_placeholder: Vec<String>,
+
+    /// Fit lines to this many columns [default: the terminal width, or no limit when not a terminal]
+    #[arg(long, value_name = "COLUMNS")]
+    pub width: Option<usize>,
+
+    /// What to do with lines wider than the terminal
+    #[arg(long, value_enum, default_value_t = Overflow::Wrap, value_name = "POLICY")]
+    pub overflow: Overflow,
+
+    /// Overflow policy for specific modules; comma-separated or repeated (e.g. --module-overflow gpu=truncate)
+    #[arg(long, value_delimiter = ',', value_name = "MODULE=POLICY", value_parser = parse_module_overflow)]
+    pub module_overflow: Vec<(String, Overflow)>,
+
+    /// How labels are lined up against each other
+    #[arg(long, value_enum, default_value_t = Align::Right)]
+    pub align: Align,
+
+    /// Text between label and value [default: ": ", or " " with --align dots]
+    #[arg(long, value_name = "STRING")]
+    pub separator: Option<String>,
+
+    /// When to use colour; auto disables it when `NO_COLOR` is set or output is not a terminal
+    #[arg(long, alias = "colour", value_enum, default_value_t = ColorChoice::Auto, value_name = "WHEN")]
+    pub color: ColorChoice,
+
+    /// Colours the terminal supports [default: detected from COLORTERM and TERM]
+    #[arg(long, value_enum, value_name = "DEPTH")]
+    pub colour_depth: Option<ColourDepth>,
+
+    /// Label colour, as a name (e.g. bright-blue) or SGR parameters (e.g. "1;34") [default: the OS colour]
+    #[arg(long, value_name = "COLOUR", value_parser = parse_colour)]
+    pub label_colour: Option<String>,
+
+    /// Value colour, as a name or SGR parameters
+    #[arg(long, value_name = "COLOUR", value_parser = parse_colour)]
+    pub value_colour: Option<String>,
+
+    /// Separator colour, as a name or SGR parameters
+    #[arg(long, value_name = "COLOUR", value_parser = parse_colour)]
+    pub separator_colour: Option<String>,
+
+    /// Label colour for specific modules; comma-separated or repeated (e.g. --module-colour gpu=green)
+    #[arg(long, value_delimiter = ',', value_name = "MODULE=COLOUR", value_parser = parse_module_colour)]
+    pub module_colour: Vec<(String, String)>,
+
+    /// Template for a module's line, referencing fields of its JSON output; repeatable (e.g. --format 'kernel={release} ({architecture})')
+    #[arg(long, value_name = "MODULE=TEMPLATE", value_parser = parse_module_template)]
+    pub format: Vec<(String, String)>,
+
+    /// Show module icons with the labels; needs a Nerd Font unless set to ascii
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "nerd", value_name = "SET")]
+    pub icons: Option<IconSet>,
+
+    /// Where icons go relative to the text label
+    #[arg(long, value_enum, default_value_t = IconPosition::Before, value_name = "POSITION")]
+    pub icon_position: IconPosition,
+
+    /// Icon for specific modules in --icons mode; comma-separated or repeated (e.g. --icon cpu=C)
+    #[arg(long, value_delimiter = ',', value_name = "MODULE=ICON", value_parser = parse_module_icon)]
+    pub icon: Vec<(String, String)>,
+
+    /// Start with a user@host title, underlined with RULE
+    #[arg(long, num_args = 0..=1, default_missing_value = "-", value_name = "RULE")]
+    pub header: Option<char>,
+
+    /// Draw usage bars for values such as memory, VRAM and battery charge
+    #[arg(long)]
+    pub bars: bool,
+
+    /// Cells in each usage bar [default: 10]
+    #[arg(long, value_name = "CELLS")]
+    pub bar_width: Option<usize>,
+
+    /// Filled and empty bar characters, as a two-character string [default: █░]
+    #[arg(long, value_name = "CHARS", value_parser = parse_bar_chars)]
+    pub bar_chars: Option<(char, char)>,
+
+    /// Usage at which bars turn to the warning colour [default: 0.7]
+    #[arg(long, value_name = "RATIO")]
+    pub bar_warning: Option<f64>,
+
+    /// Usage at which bars turn to the critical colour [default: 0.9]
+    #[arg(long, value_name = "RATIO")]
+    pub bar_critical: Option<f64>,
+
+    /// Bar colours below warning, from warning and from critical [default: green,yellow,red]
+    #[arg(long, value_name = "NORMAL,WARNING,CRITICAL", value_parser = parse_bar_colours)]
+    pub bar_colours: Option<[String; 3]>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetworkHide {
+    /// The loopback interface
+    Loopback,
+    /// Bridges, VPNs, veths and other interfaces without a physical device
+    Virtual,
+    /// MAC and IP addresses
+    Addresses,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SensorView {
+    /// One line with the CPU, GPU and drive temperatures and the fastest fan
+    #[default]
+    Summary,
+    /// The summary followed by every reading of every chip
+    Chips,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Continue on the next line, aligned under the content column
+    #[default]
+    Wrap,
+    /// Cut the line short with an ellipsis
+    Truncate,
+    /// Leave long lines for the terminal to handle
+    None,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    /// Right-align labels to the longest one
+    #[default]
+    Right,
+    /// Left-align labels, padding after them
+    Left,
+    /// Print labels as they are
+    None,
+    /// Left-align labels and fill the gap with a dotted leader
+    Dots,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Always write colour escape codes
+    Always,
+    /// Colour only when writing to a terminal and `NO_COLOR` is unset
+    #[default]
+    Auto,
+    /// Never write colour escape codes
+    Never,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColourDepth {
+    /// 24-bit colour
+    #[default]
+    #[value(name = "truecolor")]
+    TrueColour,
+    /// The xterm 256-colour palette
+    #[value(name = "256")]
+    Ansi256,
+    /// The 16 standard colours
+    #[value(name = "16")]
+    Ansi16,
+}
+
+const COLOUR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Accepts a colour name, optionally prefixed with `bright-`, or raw SGR
+/// parameters, and returns SGR parameters
+fn parse_colour(value: &str) -> Result<String, String> {
+    let lower = value.to_lowercase();
+    let (name, base) = match lower.strip_prefix("bright-") {
+        Some(name) => (name, 90),
+        None => (lower.as_str(), 30),
+    };
+    if let Some(i) = COLOUR_NAMES.iter().position(|n| *n == name) {
+        return Ok((base + i).to_string());
+    }
+    let is_sgr = !value.is_empty()
+        && value
+            .split(';')
+            .all(|p| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()));
+    if is_sgr {
+        Ok(value.to_owned())
+    } else {
+        Err(format!(
+            "expected a colour name ({}, optionally bright-) or SGR parameters such as 1;34",
+            COLOUR_NAMES.join(", ")
+        ))
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IconSet {
+    /// Nerd Font glyphs
+    #[default]
+    Nerd,
+    /// Plain-ASCII stand-ins for terminals without a Nerd Font
+    Ascii,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IconPosition {
+    /// Draw the icon before the label
+    #[default]
+    Before,
+    /// Draw the icon instead of the label
+    Replace,
+}
+
+fn parse_module_icon(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(module, icon)| (module.to_owned(), icon.to_owned()))
+        .ok_or_else(|| format!("expected MODULE=ICON, got '{value}'"))
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlockGlyph {
+    /// Background-coloured spaces
+    #[default]
+    Space,
+    /// Full blocks (█)
+    Full,
+    /// Dots (●)
+    Dot,
+    /// Half blocks (▀), packing two palette rows into each line
+    Half,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlockAlign {
+    /// Line up with the module values
+    #[default]
+    Content,
+    /// Start at the left edge, under the labels
+    Left,
+}
+
+fn parse_block_range(value: &str) -> Result<(u8, u8), String> {
+    let invalid = || format!("expected FIRST-LAST with palette indices 0 to 255, got '{value}'");
+    let (first, last) = value.split_once('-').ok_or_else(invalid)?;
+    let first: u8 = first.trim().parse().map_err(|_| invalid())?;
+    let last: u8 = last.trim().parse().map_err(|_| invalid())?;
+    if first > last {
+        return Err(format!("range starts after it ends: '{value}'"));
+    }
+    Ok((first, last))
+}
+
+fn parse_bar_chars(value: &str) -> Result<(char, char), String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(fill), Some(empty), None) => Ok((fill, empty)),
+        _ => Err(format!("expected two characters, got '{value}'")),
+    }
+}
+
+fn parse_bar_colours(value: &str) -> Result<[String; 3], String> {
+    let colours = value
+        .split(',')
+        .map(parse_colour)
+        .collect::<Result<Vec<_>, _>>()?;
+    colours
+        .try_into()
+        .map_err(|_| format!("expected three comma-separated colours, got '{value}'"))
+}
+
+fn parse_module_colour(value: &str) -> Result<(String, String), String> {
+    let (module, colour) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected MODULE=COLOUR, got '{value}'"))?;
+    Ok((module.to_owned(), parse_colour(colour)?))
+}
+
+fn parse_module_template(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(module, template)| (module.to_owned(), template.to_owned()))
+        .ok_or_else(|| format!("expected MODULE=TEMPLATE, got '{value}'"))
+}
+
+fn parse_module_overflow(value: &str) -> Result<(String, Overflow), String> {
+    let (module, policy) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected MODULE=POLICY, got '{value}'"))?;
+    let policy = Overflow::from_str(policy, true)?;
+    Ok((module.to_owned(), policy))
 }
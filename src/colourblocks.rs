@@ -1,23 +1,115 @@
-const COLOUR_RESET: &str = "\x1b[0m";
-//use std::io::Write;
 use std::fmt::Write;
 
-pub fn colourblocks(indent: usize, colours: usize, line_length: usize) -> String {
-    let mut blocks = String::new();
-    append_spaces(&mut blocks, indent);
-    for i in 0..colours {
-        if (i % line_length == 0) && (i != 0) {
-            blocks.push_str(COLOUR_RESET);
-            blocks.push('\n');
-            append_spaces(&mut blocks, indent);
+use crate::args::{BlockAlign, BlockGlyph};
+use crate::fetch::{downsample, ColourDepth};
+
+const COLOUR_RESET: &str = "\x1b[0m";
+
+/// The palette strip drawn under the modules
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Blocks {
+    /// First and last palette index shown
+    pub range: (u8, u8),
+    /// Palette colours per row
+    pub per_row: usize,
+    pub glyph: BlockGlyph,
+    /// Columns per colour
+    pub width: usize,
+    pub align: BlockAlign,
+}
+
+impl Default for Blocks {
+    fn default() -> Self {
+        Self {
+            range: (0, 15),
+            per_row: 8,
+            glyph: BlockGlyph::Space,
+            width: 3,
+            align: BlockAlign::Content,
         }
-        write!(&mut blocks, "\x1b[38;5;{i}m\x1b[48;5;{i}m   ")
-            .expect("Could not write colourblocks for some reason");
     }
-    blocks.push_str(COLOUR_RESET);
-    blocks
 }
 
-fn append_spaces(s: &mut String, count: usize) {
-    let _ = write!(s, "{:indent$}", "", indent = count);
+impl Blocks {
+    /// Renders the strip, indented to `content_column` when aligned with the
+    /// module content
+    pub fn render(&self, content_column: usize, depth: ColourDepth) -> String {
+        let indent = match self.align {
+            BlockAlign::Content => content_column,
+            BlockAlign::Left => 0,
+        };
+        let colours: Vec<u8> = (self.range.0..=self.range.1).collect();
+        let rows: Vec<&[u8]> = colours.chunks(self.per_row.max(1)).collect();
+        // Half blocks pack two palette rows into each line of text.
+        let lines: Vec<String> = match self.glyph {
+            BlockGlyph::Half => rows
+                .chunks(2)
+                .map(|pair| self.row(pair[0], pair.get(1).copied(), depth))
+                .collect(),
+            _ => rows.iter().map(|row| self.row(row, None, depth)).collect(),
+        };
+        lines
+            .iter()
+            .map(|line| format!("{:indent$}{line}{COLOUR_RESET}", ""))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn row(&self, top: &[u8], bottom: Option<&[u8]>, depth: ColourDepth) -> String {
+        let mut row = String::new();
+        for (n, colour) in top.iter().enumerate() {
+            let below = bottom.and_then(|b| b.get(n)).copied();
+            let (sgr, cell) = self.cell(*colour, below);
+            let _ = write!(row, "\x1b[{}m{cell}", downsample(&sgr, depth));
+        }
+        row
+    }
+
+    /// SGR parameters and text for one colour; `below` is the colour of the
+    /// lower half in half-block mode
+    fn cell(&self, colour: u8, below: Option<u8>) -> (String, String) {
+        let fg = format!("38;5;{colour}");
+        match self.glyph {
+            BlockGlyph::Space => (format!("{fg};48;5;{colour}"), " ".repeat(self.width)),
+            BlockGlyph::Full => (fg, "█".repeat(self.width)),
+            BlockGlyph::Dot => (fg, format!("●{}", " ".repeat(self.width.saturating_sub(1)))),
+            BlockGlyph::Half => {
+                // Without a lower row the bottom half keeps the default background.
+                let sgr = match below {
+                    Some(b) => format!("{fg};48;5;{b}"),
+                    None => format!("{fg};49"),
+                };
+                (sgr, "▀".repeat(self.width))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_blocks() {
+        let blocks = Blocks::default().render(2, ColourDepth::TrueColour);
+        let lines: Vec<&str> = blocks.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("  \x1b[38;5;0;48;5;0m   \x1b[38;5;1;48;5;1m   "));
+        assert!(lines[1].ends_with("\x1b[38;5;15;48;5;15m   \x1b[0m"));
+    }
+
+    #[test]
+    fn half_blocks() {
+        let blocks = Blocks {
+            range: (0, 4),
+            per_row: 2,
+            glyph: BlockGlyph::Half,
+            width: 1,
+            align: BlockAlign::Left,
+        };
+        assert_eq!(
+            blocks.render(10, ColourDepth::Ansi16),
+            "\x1b[30;42m▀\x1b[31;43m▀\x1b[0m\n\x1b[34;49m▀\x1b[0m"
+        );
+    }
 }
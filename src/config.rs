@@ -0,0 +1,51 @@
+use std::path::{Path, PathBuf};
+
+/// Options handed to modules while they load. Built in `main` from the
+/// command line and passed through `Machine::new` to every module registered
+/// with the `config` attribute, and to `Fetch::configure` when modules are
+/// displayed.
+#[derive(Default, Clone)]
+pub struct Config {
+    /// Root directory that system files are read from; `None` means `/`.
+    pub sysroot: Option<PathBuf>,
+    pub network: NetworkConfig,
+    pub load: LoadConfig,
+    /// List every hwmon chip under the sensors summary
+    pub sensor_chips: bool,
+    /// DMI values to treat as unset, on top of the built-in placeholders
+    pub dmi_placeholders: Vec<String>,
+}
+
+#[derive(Default, Clone)]
+pub struct NetworkConfig {
+    pub hide_loopback: bool,
+    pub hide_virtual: bool,
+    pub hide_addresses: bool,
+}
+
+/// Per-core one-minute load at which the load module changes colour
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoadConfig {
+    pub warning: f64,
+    pub critical: f64,
+}
+
+impl Default for LoadConfig {
+    fn default() -> Self {
+        Self {
+            warning: 0.7,
+            critical: 1.0,
+        }
+    }
+}
+
+impl Config {
+    /// Maps an absolute path on the target system to where it lives under the sysroot
+    pub fn sysroot_path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        let path = path.as_ref();
+        match &self.sysroot {
+            Some(root) => root.join(path.strip_prefix("/").unwrap_or(path)),
+            None => path.to_path_buf(),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! DMI (SMBIOS) strings as exposed in sysfs, and the placeholder values that
+//! firmware vendors leave in unset fields. Modules save the raw strings and
+//! drop placeholders when displayed, so `--dmi-placeholder` also applies to
+//! saved machines.
+
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+
+const DMI_DIR: &str = "/sys/devices/virtual/dmi/id";
+
+/// Values seen in unset DMI fields, compared case-insensitively.
+const PLACEHOLDERS: [&str; 20] = [
+    "To Be Filled By O.E.M.",
+    "To Be Filled By O.E.M",
+    "Default string",
+    "System Product Name",
+    "System Version",
+    "System manufacturer",
+    "System Serial Number",
+    "System SKUNumber",
+    "Base Board Product Name",
+    "Base Board Version",
+    "Chassis Manufacturer",
+    "Chassis Version",
+    "Not Applicable",
+    "Not Specified",
+    "Not Available",
+    "None",
+    "N/A",
+    "O.E.M.",
+    "OEM",
+    "0123456789",
+];
+
+/// Reads a DMI field such as `product_name`, returning `None` if it is
+/// missing or empty
+pub fn read_field(field: &str) -> Option<String> {
+    let value = fs::read_to_string(Path::new(DMI_DIR).join(field)).ok()?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+/// Clears `value` if it is a placeholder
+pub fn clear_placeholder(value: &mut Option<String>, config: &Config) {
+    if value.as_deref().is_some_and(|v| is_placeholder(v, config)) {
+        *value = None;
+    }
+}
+
+/// Whether `value` is a built-in placeholder or one added in the config
+pub fn is_placeholder(value: &str, config: &Config) -> bool {
+    let value = value.trim();
+    PLACEHOLDERS
+        .iter()
+        .copied()
+        .chain(config.dmi_placeholders.iter().map(String::as_str))
+        .any(|p| p.eq_ignore_ascii_case(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders() {
+        let config = Config {
+            dmi_placeholders: vec!["Unknown Board".into()],
+            ..Config::default()
+        };
+        assert!(is_placeholder("To be filled by O.E.M.", &config));
+        assert!(is_placeholder("default STRING ", &config));
+        assert!(is_placeholder("unknown board", &config));
+        assert!(!is_placeholder("PRIME X570-PRO", &config));
+    }
+}
@@ -35,4 +35,10 @@ pub enum Error {
     ZVariant(#[from] zbus::zvariant::Error),
     #[error("Memory device error")]
     Memdev(#[from] memdev::Error),
+    #[error("Malformed netlink message")]
+    Netlink,
+    #[error("Malformed SQLite database")]
+    Sqlite,
+    #[error("Invalid template for {module}: {reason}")]
+    Template { module: String, reason: String },
 }
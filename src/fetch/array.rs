@@ -1,10 +1,15 @@
-use crate::fetch::Line;
+use crate::fetch::{Bars, Line, Style, Theme};
 
 use super::Fetch;
 
 pub struct Array {
     sections: Vec<Line>,
-    colour: Option<String>,
+    theme: Theme,
+    /// Terminal columns to fit lines into; `None` for no limit
+    width: Option<usize>,
+    style: Style,
+    /// Usage bars for lines with a ratio; `None` leaves them off
+    bars: Option<Bars>,
 }
 
 impl Default for Array {
@@ -17,12 +22,27 @@ impl Array {
     pub fn new() -> Self {
         Array {
             sections: Vec::new(),
-            colour: None,
+            theme: Theme::default(),
+            width: None,
+            style: Style::default(),
+            bars: None,
         }
     }
 
-    pub fn set_colour(&mut self, colour: Option<String>) {
-        self.colour = colour;
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn set_width(&mut self, width: Option<usize>) {
+        self.width = width;
+    }
+
+    pub fn set_style(&mut self, style: Style) {
+        self.style = style;
+    }
+
+    pub fn set_bars(&mut self, bars: Option<Bars>) {
+        self.bars = bars;
     }
 
     pub fn push<T: Into<Line>>(&mut self, value: T) {
@@ -54,6 +74,11 @@ impl Array {
         indent
     }
 
+    /// Column the content of the widest label starts at
+    pub fn content_column(&self) -> usize {
+        self.style.content_column(self.get_indent())
+    }
+
     pub fn append(&mut self, value: &mut Self) {
         self.sections.append(&mut value.sections);
     }
@@ -63,7 +88,10 @@ impl From<Vec<Line>> for Array {
     fn from(value: Vec<Line>) -> Self {
         Self {
             sections: value,
-            colour: None,
+            theme: Theme::default(),
+            width: None,
+            style: Style::default(),
+            bars: None,
         }
     }
 }
@@ -73,7 +101,14 @@ impl std::fmt::Display for Array {
         let indent = self.get_indent();
         let mut iter = self.sections.iter().peekable();
         while let Some(line) = iter.next() {
-            line.fmt(indent, f, self.colour.clone())?;
+            line.fmt(
+                indent,
+                self.width,
+                &self.style,
+                &self.theme,
+                self.bars.as_ref(),
+                f,
+            )?;
             if iter.peek().is_some() {
                 writeln!(f)?;
             }
@@ -81,3 +116,63 @@ impl std::fmt::Display for Array {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetch::{display_width, Align, Overflow, SEPARATOR};
+
+    #[test]
+    fn mixed_script_alignment() {
+        let array = Array::from(vec![
+            Line::from(("CPU", "x")),
+            Line::from(("Mémoire", "x")),
+            Line::from(("メモリ", "x")),
+            Line::from(("🔋 Akku", "x")),
+            Line::from(("\x1b[1mGPU\x1b[0m", "x")),
+        ]);
+        assert_eq!(array.get_indent(), 7);
+        for row in array.to_string().lines() {
+            let (label, _) = row.split_once(SEPARATOR).unwrap_or_default();
+            assert_eq!(display_width(label), 7, "{row:?}");
+        }
+    }
+
+    #[test]
+    fn wrapped_rows_align_with_content() {
+        let mut array = Array::from(vec![
+            Line::from(("GPU", "NVIDIA GeForce RTX 4090, AMD Radeon Graphics")),
+            Line::from(("Model", "ASUSTeK COMPUTER INC. PRIME X570-PRO"))
+                .with_overflow(Overflow::Truncate),
+        ]);
+        array.set_width(Some(31));
+        assert_eq!(
+            array.to_string(),
+            "  GPU: NVIDIA GeForce RTX 4090,\n       AMD Radeon Graphics\nModel: ASUSTeK COMPUTER INC. P…"
+        );
+    }
+
+    #[test]
+    fn label_styles() {
+        let render = |style| {
+            let mut array = Array::from(vec![
+                Line::from(("CPU", "Ryzen")),
+                Line::from(("Model", "X570")),
+            ]);
+            array.set_style(style);
+            array.to_string()
+        };
+        assert_eq!(
+            render(Style::new(Align::Left, Some(" | ".into()))),
+            "CPU   | Ryzen\nModel | X570"
+        );
+        assert_eq!(
+            render(Style::new(Align::None, None)),
+            "CPU: Ryzen\nModel: X570"
+        );
+        assert_eq!(
+            render(Style::new(Align::Dots, None)),
+            "CPU ..... Ryzen\nModel ... X570"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use super::Theme;
+
+/// How full something is, as a structured alternative to parsing it back out
+/// of a display string
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ratio {
+    value: f64,
+    /// True where a low value is the bad end, e.g. battery charge
+    inverted: bool,
+}
+
+impl Ratio {
+    /// Share of a resource in use, where fuller is worse
+    pub fn used(used: f64, total: f64) -> Option<Self> {
+        (total > 0.0).then(|| Self {
+            value: (used / total).clamp(0.0, 1.0),
+            inverted: false,
+        })
+    }
+
+    /// A level between 0 and 1 where emptier is worse
+    pub fn level(value: f64) -> Self {
+        Self {
+            value: value.clamp(0.0, 1.0),
+            inverted: true,
+        }
+    }
+
+    pub fn value(self) -> f64 {
+        self.value
+    }
+
+    /// How close to the bad end this is, for picking a colour
+    fn pressure(self) -> f64 {
+        if self.inverted {
+            1.0 - self.value
+        } else {
+            self.value
+        }
+    }
+}
+
+/// Inline usage bars such as `[██████░░░░]  62% `, drawn in front of the
+/// content of lines that carry a `Ratio`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bars {
+    /// Cells between the brackets
+    pub width: usize,
+    pub fill: char,
+    pub empty: char,
+    /// Pressure from which the bar takes the warning colour
+    pub warning: f64,
+    /// Pressure from which the bar takes the critical colour
+    pub critical: f64,
+    /// Colours below warning, from warning, and from critical, as SGR parameters
+    pub colours: [String; 3],
+}
+
+impl Default for Bars {
+    fn default() -> Self {
+        Self {
+            width: 10,
+            fill: '█',
+            empty: '░',
+            warning: 0.7,
+            critical: 0.9,
+            colours: ["32".into(), "33".into(), "31".into()],
+        }
+    }
+}
+
+impl Bars {
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn render(&self, ratio: Ratio, theme: &Theme) -> String {
+        let filled = ((ratio.value * self.width as f64).round() as usize).min(self.width);
+        let pressure = ratio.pressure();
+        let colour = if pressure >= self.critical {
+            &self.colours[2]
+        } else if pressure >= self.warning {
+            &self.colours[1]
+        } else {
+            &self.colours[0]
+        };
+        let fill: String = std::iter::repeat_n(self.fill, filled).collect();
+        let empty: String = std::iter::repeat_n(self.empty, self.width - filled).collect();
+        format!(
+            "[{}{empty}] {:>3.0}% ",
+            theme.paint(Some(colour), &fill),
+            ratio.value * 100.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn bars() {
+        let bars = Bars::default();
+        let plain = Theme::plain();
+        let memory = Ratio::used(62.0, 100.0).unwrap();
+        assert_eq!(bars.render(memory, &plain), "[██████░░░░]  62% ");
+        assert_eq!(bars.render(Ratio::level(1.0), &plain), "[██████████] 100% ");
+        assert_eq!(Ratio::used(1.0, 0.0), None);
+
+        // A nearly flat battery is critical; nearly full memory is too.
+        let theme = Theme::default();
+        assert!(bars.render(Ratio::level(0.05), &theme).contains("\x1b[31m"));
+        assert!(bars.render(Ratio::level(0.95), &theme).contains("\x1b[32m"));
+        let full = Ratio::used(95.0, 100.0).unwrap();
+        assert!(bars.render(full, &theme).contains("\x1b[31m"));
+    }
+}
@@ -1,21 +1,133 @@
 use std::fmt::Display;
 
-use std::fmt::Write;
-
 use crate::Error;
 use crate::Result;
 
-use super::Fetch;
+use super::width::{display_width, pad_left, pad_right, truncate, wrap};
+use super::{Bars, Fetch, Ratio, Theme};
 
+/// Default text between label and content
 pub const SEPARATOR: &str = ": ";
+
+/// Fewest dots in a leader, so the longest label still gets one
+const MIN_LEADER: usize = 3;
+
+/// Below this many columns for content, wrapping or truncating would leave
+/// lines unreadable, so they are printed whole.
+const MIN_CONTENT_WIDTH: usize = 10;
+
+/// How strongly a line's content should stand out, e.g. a temperature
+/// nearing its critical threshold.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    #[default]
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    fn colour(self) -> Option<&'static str> {
+        match self {
+            Self::Normal => None,
+            Self::Warning => Some("33"),
+            Self::Critical => Some("31"),
+        }
+    }
+}
+
+/// Handling of content wider than the space left after the label
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Continued on the next line, aligned under the content column
+    #[default]
+    Wrap,
+    /// Cut short with an ellipsis
+    Truncate,
+    /// Left for the terminal to handle
+    None,
+}
+
+/// Label alignment within the label column
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    /// Right-aligned to the longest label
+    #[default]
+    Right,
+    /// Left-aligned, padded after the label
+    Left,
+    /// Printed as they are
+    None,
+    /// Left-aligned, with a dotted leader filling the gap
+    Dots,
+}
+
+/// How labels are laid out relative to each other and their content
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Style {
+    pub align: Align,
+    pub separator: String,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self::new(Align::default(), None)
+    }
+}
+
+impl Style {
+    /// A dotted leader already separates label and content, so it defaults to
+    /// a plain space rather than `SEPARATOR`.
+    pub fn new(align: Align, separator: Option<String>) -> Self {
+        let separator = separator.unwrap_or_else(|| match align {
+            Align::Dots => " ".to_owned(),
+            _ => SEPARATOR.to_owned(),
+        });
+        Self { align, separator }
+    }
+
+    /// The label as printed, given the widest label in the array
+    fn label(&self, name: &str, indent: usize) -> String {
+        match self.align {
+            Align::Right => pad_left(name, indent),
+            Align::Left => pad_right(name, indent),
+            Align::None => name.to_owned(),
+            Align::Dots => {
+                let dots = indent.saturating_sub(display_width(name)) + MIN_LEADER;
+                format!("{name} {}", ".".repeat(dots))
+            }
+        }
+    }
+
+    /// Column content starts at for a label `indent` columns wide
+    pub fn content_column(&self, indent: usize) -> usize {
+        let label = match self.align {
+            Align::Dots => indent + 1 + MIN_LEADER,
+            _ => indent,
+        };
+        label + display_width(&self.separator)
+    }
+}
+
 /// Simple fetching program
 pub struct Line {
     pub name: String,
     pub content: String,
+    pub severity: Severity,
+    /// Handling of content wider than the space left after the label
+    pub overflow: Overflow,
+    /// Label colour, overriding the theme's
+    pub colour: Option<String>,
+    /// How full the value is, drawn as a bar when bars are enabled
+    pub ratio: Option<Ratio>,
 }
 
 impl Line {
-    /// Write the section to provided formatter
+    /// Write the section to provided formatter, laying out the label with
+    /// `style` against the widest label `indent` and colouring it with
+    /// `theme`. Lines with a ratio get a usage bar when `bars` is set. When
+    /// `width` is set, content that does not fit is wrapped or truncated
+    /// according to `overflow`.
     ///
     /// # Errors
     ///
@@ -23,24 +135,81 @@ impl Line {
     pub fn fmt(
         &self,
         indent: usize,
+        width: Option<usize>,
+        style: &Style,
+        theme: &Theme,
+        bars: Option<&Bars>,
         f: &mut std::fmt::Formatter<'_>,
-        colour: Option<String>,
     ) -> std::fmt::Result {
-        let name_text = format!("{:>indent$}", self.name);
-        let name_coloured = match colour {
-            Some(s) => {
-                let mut t = String::new();
-                write!(t, "\x1b[{s}m{name_text}\x1b[0m")?;
-                t
-            }
-            None => name_text,
+        let label_colour = self.colour.as_deref().or(theme.label.as_deref());
+        write!(
+            f,
+            "{}{}",
+            theme.paint(label_colour, &style.label(&self.name, indent)),
+            theme.paint(theme.separator.as_deref(), &style.separator)
+        )?;
+
+        // Unaligned labels each start their content at a different column.
+        let content_column = match style.align {
+            Align::None => style.content_column(self.get_indent()),
+            _ => style.content_column(indent),
         };
-        write!(f, "{}{}{}", name_coloured, SEPARATOR, self.content,)?;
+        let bar = bars
+            .zip(self.ratio)
+            .map(|(bars, ratio)| bars.render(ratio, theme))
+            .unwrap_or_default();
+        let rows = self.content_rows(width, content_column + display_width(&bar));
+        for (i, row) in rows.iter().enumerate() {
+            if i == 0 {
+                write!(f, "{bar}")?;
+            } else {
+                write!(f, "\n{:content_column$}", "")?;
+            }
+            let value_colour = self.severity.colour().or(theme.value.as_deref());
+            write!(f, "{}", theme.paint(value_colour, row))?;
+        }
         Ok(())
     }
 
+    /// Splits or cuts the content to fit in the columns right of `content_column`
+    fn content_rows(&self, width: Option<usize>, content_column: usize) -> Vec<String> {
+        let available = width
+            .and_then(|w| w.checked_sub(content_column))
+            .filter(|a| *a >= MIN_CONTENT_WIDTH);
+        match (available, self.overflow) {
+            (Some(a), Overflow::Wrap) => wrap(&self.content, a),
+            (Some(a), Overflow::Truncate) => vec![truncate(&self.content, a)],
+            _ => vec![self.content.clone()],
+        }
+    }
+
+    #[must_use]
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    #[must_use]
+    pub fn with_colour(mut self, colour: Option<String>) -> Self {
+        self.colour = colour;
+        self
+    }
+
+    #[must_use]
+    pub fn with_ratio(mut self, ratio: Option<Ratio>) -> Self {
+        self.ratio = ratio;
+        self
+    }
+
+    #[must_use]
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Display width of the label, ignoring escape sequences
     pub fn get_indent(&self) -> usize {
-        self.name.len()
+        display_width(&self.name)
     }
 }
 
@@ -49,6 +218,10 @@ impl<A: Display, B: Display> From<(A, B)> for Line {
         Self {
             name: name.to_string(),
             content: content.to_string(),
+            severity: Severity::default(),
+            overflow: Overflow::default(),
+            colour: None,
+            ratio: None,
         }
     }
 }
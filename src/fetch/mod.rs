@@ -1,15 +1,31 @@
 mod array;
+mod bar;
 mod line;
+mod sgr;
+mod template;
+mod theme;
+mod width;
 
 use std::fmt::Display;
 
+use crate::config::Config;
+
 pub use array::Array;
+pub use bar::{Bars, Ratio};
 pub use fetch_derive::Fetch;
-pub use line::{Line, SEPARATOR};
+pub use line::{Align, Line, Overflow, Severity, Style, SEPARATOR};
+pub use sgr::{detect_depth, downsample, ColourDepth};
+pub use template::Template;
+pub use theme::Theme;
+pub use width::{display_width, pad_left, pad_right, strip_ansi};
 
 pub trait Fetch: Display + Clone {
     fn name(&self) -> &'static str;
 
+    /// Applies load-time options that are not saved with the module, so a
+    /// machine read back with `--input` is shown with the current ones
+    fn configure(&mut self, _config: &Config) {}
+
     fn as_fetchlines(&self) -> Vec<Line> {
         vec![self.clone().into()]
     }
@@ -22,16 +38,16 @@ pub trait Fetch: Display + Clone {
 /// `load_module` is the only method modules need to implement; the rest are
 /// derived from it and the Serialize/Deserialize bounds.
 pub trait DynModule: serde::Serialize + for<'de> serde::Deserialize<'de> + Fetch + Sized {
-    fn load_module() -> Option<Self>;
+    fn load_module(config: &Config) -> Option<Self>;
 
-    fn load_dyn() -> Option<serde_json::Value> {
-        Self::load_module().and_then(|v| serde_json::to_value(&v).ok())
+    fn load_dyn(config: &Config) -> Option<serde_json::Value> {
+        Self::load_module(config).and_then(|v| serde_json::to_value(&v).ok())
     }
 
-    fn display_dyn(val: serde_json::Value) -> Option<Vec<Line>> {
-        serde_json::from_value::<Self>(val)
-            .ok()
-            .map(|v| v.as_fetchlines())
+    fn display_dyn(val: serde_json::Value, config: &Config) -> Option<Vec<Line>> {
+        let mut module = serde_json::from_value::<Self>(val).ok()?;
+        module.configure(config);
+        Some(module.as_fetchlines())
     }
 
     // Returns None for every module except OsInfo, which overrides this via
@@ -45,10 +61,16 @@ pub trait DynModule: serde::Serialize + for<'de> serde::Deserialize<'de> + Fetch
 /// Machine iterates these at runtime — adding a module requires no changes here.
 pub struct ModuleRegistration {
     pub key: &'static str,
+    /// Sort position, lowest first. Spaced ten apart so a new module can
+    /// be slotted between two others without renumbering them.
     pub priority: u32,
-    pub load: fn() -> Option<serde_json::Value>,
-    pub display: fn(serde_json::Value) -> Option<Vec<Line>>,
+    pub load: fn(&Config) -> Option<serde_json::Value>,
+    pub display: fn(serde_json::Value, &Config) -> Option<Vec<Line>>,
     pub colour: fn(&serde_json::Value) -> Option<String>,
+    /// Nerd Font glyph for `--icons`
+    pub icon: Option<&'static str>,
+    /// Plain-ASCII icon for `--icons ascii`
+    pub ascii_icon: Option<&'static str>,
 }
 
 // Must be called exactly once per type in the crate; pairing it with the
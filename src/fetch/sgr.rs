@@ -0,0 +1,245 @@
+//! Parsing SGR parameter strings (the part between `ESC [` and `m`) and
+//! rewriting their colours for terminals with fewer colours.
+
+type Rgb = (u8, u8, u8);
+
+/// How many colours the terminal can show
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColourDepth {
+    /// 24-bit colour
+    #[default]
+    TrueColour,
+    /// The xterm 256-colour palette
+    Ansi256,
+    /// The 16 standard colours
+    Ansi16,
+}
+
+/// A colour as given in an SGR sequence
+#[derive(Clone, Copy)]
+enum Colour {
+    Indexed(u8),
+    Rgb(Rgb),
+}
+
+/// Channel values of the 6x6x6 cube in the xterm 256-colour palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The xterm defaults for the 16 standard colours
+const ANSI_16: [Rgb; 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// `TERM` values of terminals with 24-bit colour that do not set `COLORTERM`
+/// everywhere, e.g. over SSH, where it is not passed on by default
+const TRUECOLOUR_TERMS: [&str; 7] = [
+    "alacritty",
+    "contour",
+    "foot",
+    "foot-extra",
+    "wezterm",
+    "xterm-ghostty",
+    "xterm-kitty",
+];
+
+/// `TERM` values of terminals limited to the 16 standard colours or fewer
+const BASIC_TERMS: [&str; 5] = ["ansi", "cons25", "dumb", "linux", "xterm-color"];
+
+/// Guesses how many colours the terminal supports from `COLORTERM` and `TERM`
+pub fn detect_depth() -> ColourDepth {
+    depth_from_env(
+        std::env::var("COLORTERM").ok().as_deref(),
+        std::env::var("TERM").ok().as_deref(),
+    )
+}
+
+/// Terminals not known either way are assumed to have the 256-colour
+/// palette, which nearly every terminal emulator in use supports.
+fn depth_from_env(colorterm: Option<&str>, term: Option<&str>) -> ColourDepth {
+    if matches!(colorterm, Some("truecolor" | "24bit")) {
+        return ColourDepth::TrueColour;
+    }
+    match term {
+        Some(t)
+            if TRUECOLOUR_TERMS.contains(&t)
+                || t.ends_with("-direct")
+                || t.contains("truecolor") =>
+        {
+            ColourDepth::TrueColour
+        }
+        Some(t) if BASIC_TERMS.contains(&t) || t.ends_with("-16color") || t.starts_with("vt") => {
+            ColourDepth::Ansi16
+        }
+        Some(_) => ColourDepth::Ansi256,
+        None => ColourDepth::Ansi16,
+    }
+}
+
+/// Rewrites the extended colours in `params` (`38;2;r;g;b`, `38;5;n` and
+/// their `48` background forms) to fit `depth`. Other parameters are kept.
+pub fn downsample(params: &str, depth: ColourDepth) -> String {
+    if depth == ColourDepth::TrueColour {
+        return params.to_owned();
+    }
+    let tokens: Vec<&str> = params.split(';').collect();
+    let mut out: Vec<String> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let background = match tokens[i] {
+            "38" => false,
+            "48" => true,
+            other => {
+                out.push(other.to_owned());
+                i += 1;
+                continue;
+            }
+        };
+        let num = |j: usize| tokens.get(j).and_then(|t| t.parse::<u8>().ok());
+        let colour = match tokens.get(i + 1) {
+            Some(&"2") => match (num(i + 2), num(i + 3), num(i + 4)) {
+                (Some(r), Some(g), Some(b)) => Some((Colour::Rgb((r, g, b)), 5)),
+                _ => None,
+            },
+            Some(&"5") => num(i + 2).map(|n| (Colour::Indexed(n), 3)),
+            _ => None,
+        };
+        if let Some((colour, used)) = colour {
+            out.push(colour_params(colour, background, depth));
+            i += used;
+        } else {
+            // Malformed; pass it through for the terminal to ignore
+            out.push(tokens[i].to_owned());
+            i += 1;
+        }
+    }
+    out.join(";")
+}
+
+/// Parameters selecting the closest match for `colour` at `depth`
+fn colour_params(colour: Colour, background: bool, depth: ColourDepth) -> String {
+    if depth == ColourDepth::Ansi16 {
+        let n = match colour {
+            Colour::Indexed(index) if index < 16 => index,
+            Colour::Indexed(index) => nearest_16(palette_rgb(index)),
+            Colour::Rgb(rgb) => nearest_16(rgb),
+        };
+        let base = match (background, n < 8) {
+            (false, true) => 30,
+            (false, false) => 90 - 8,
+            (true, true) => 40,
+            (true, false) => 100 - 8,
+        };
+        (base + n).to_string()
+    } else {
+        let index = match colour {
+            Colour::Indexed(index) => index,
+            Colour::Rgb(rgb) => nearest_256(rgb),
+        };
+        format!("{};5;{index}", if background { 48 } else { 38 })
+    }
+}
+
+fn distance((r1, g1, b1): Rgb, (r2, g2, b2): Rgb) -> u32 {
+    [(r1, r2), (g1, g2), (b1, b2)]
+        .iter()
+        .map(|(a, b)| u32::from(a.abs_diff(*b)).pow(2))
+        .sum()
+}
+
+/// Closest entry in the colour cube or the grey ramp
+fn nearest_256(rgb: Rgb) -> u8 {
+    let level = |c: u8| {
+        (0u8..6)
+            .min_by_key(|i| CUBE_LEVELS[usize::from(*i)].abs_diff(c))
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = 16 + 36 * r + 6 * g + b;
+    // Greys run from 8 to 238 in steps of 10 as indices 232..=255.
+    let grey = (0u8..24)
+        .min_by_key(|i| distance(rgb, (8 + 10 * i, 8 + 10 * i, 8 + 10 * i)))
+        .unwrap_or(0);
+    if distance(rgb, palette_rgb(232 + grey)) < distance(rgb, palette_rgb(cube)) {
+        232 + grey
+    } else {
+        cube
+    }
+}
+
+/// RGB of a 256-colour palette entry
+fn palette_rgb(index: u8) -> Rgb {
+    match index {
+        0..=15 => ANSI_16[usize::from(index)],
+        16..=231 => {
+            let i = index - 16;
+            let level = |n: u8| CUBE_LEVELS[usize::from(n)];
+            (level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        _ => {
+            let v = 8 + 10 * (index - 232);
+            (v, v, v)
+        }
+    }
+}
+
+fn nearest_16(rgb: Rgb) -> u8 {
+    (0u8..16)
+        .min_by_key(|i| distance(rgb, ANSI_16[usize::from(*i)]))
+        .unwrap_or(7)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NIXOS: &str = "0;38;2;126;186;228";
+
+    #[test]
+    fn detection() {
+        assert_eq!(
+            depth_from_env(Some("truecolor"), Some("xterm-256color")),
+            ColourDepth::TrueColour
+        );
+        assert_eq!(
+            depth_from_env(None, Some("tmux-256color")),
+            ColourDepth::Ansi256
+        );
+        assert_eq!(
+            depth_from_env(None, Some("xterm-kitty")),
+            ColourDepth::TrueColour
+        );
+        assert_eq!(depth_from_env(None, Some("screen")), ColourDepth::Ansi256);
+        assert_eq!(depth_from_env(None, Some("linux")), ColourDepth::Ansi16);
+        assert_eq!(depth_from_env(None, Some("vt220")), ColourDepth::Ansi16);
+        assert_eq!(depth_from_env(None, None), ColourDepth::Ansi16);
+    }
+
+    #[test]
+    fn downsampling() {
+        assert_eq!(downsample(NIXOS, ColourDepth::TrueColour), NIXOS);
+        assert_eq!(downsample(NIXOS, ColourDepth::Ansi256), "0;38;5;110");
+        assert_eq!(downsample(NIXOS, ColourDepth::Ansi16), "0;94");
+        assert_eq!(
+            downsample("1;48;2;30;30;30", ColourDepth::Ansi256),
+            "1;48;5;234"
+        );
+        assert_eq!(downsample("38;5;3;48;5;9", ColourDepth::Ansi16), "33;101");
+        assert_eq!(downsample("1;34", ColourDepth::Ansi16), "1;34");
+        assert_eq!(downsample("38;5", ColourDepth::Ansi16), "38;5");
+    }
+}
@@ -0,0 +1,142 @@
+use serde_json::Value;
+
+/// A user-supplied format for a module's line, such as
+/// `"{release} ({architecture})"`. Placeholders name fields of the module's
+/// serialized JSON; nested fields and array items are reached with dots, as in
+/// `{meminfo.total}` or `{devices.0.name}`. `{{` and `}}` are literal braces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Field(String),
+}
+
+impl Template {
+    /// # Errors
+    /// Returns a description of the problem if braces are unbalanced or a
+    /// placeholder is empty
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| format!("unclosed '{{' in \"{template}\""))?;
+                    let field = rest[..end].trim();
+                    if field.is_empty() || field.contains('{') {
+                        return Err(format!("empty or nested placeholder in \"{template}\""));
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Field(field.to_owned()));
+                    chars = rest[end + 1..].chars();
+                }
+                '}' => return Err(format!("unmatched '}}' in \"{template}\"; use '}}}}'")),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+
+    /// Fills in the placeholders from `value`
+    ///
+    /// # Errors
+    /// Names the first placeholder that is not a field of `value`, along with
+    /// the fields that are
+    pub fn render(&self, value: &Value) -> Result<String, String> {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => out.push_str(text),
+                Part::Field(path) => {
+                    let field = lookup(value, path).ok_or_else(|| unknown(path, value))?;
+                    out.push_str(&text(field));
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |v, key| match v {
+        Value::Object(map) => map.get(key),
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+fn unknown(path: &str, value: &Value) -> String {
+    match value {
+        Value::Object(map) => format!(
+            "unknown placeholder {{{path}}}; available fields: {}",
+            map.keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        _ => format!("unknown placeholder {{{path}}}"),
+    }
+}
+
+/// Strings are written bare, missing values as nothing, and lists of
+/// scalars comma-separated; anything else falls back to compact JSON.
+fn text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.iter().all(|i| !i.is_array() && !i.is_object()) => {
+            items.iter().map(text).collect::<Vec<_>>().join(", ")
+        }
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn templates() {
+        let kernel = json!({"release": "6.18.31", "architecture": "x86_64", "name": "Linux"});
+        let template = Template::parse("{release} ({architecture}) {{raw}}").unwrap();
+        assert_eq!(template.render(&kernel).unwrap(), "6.18.31 (x86_64) {raw}");
+
+        let gpu = json!({"devices": [{"name": "Navi 22", "vram_total": null}]});
+        let template = Template::parse("{devices.0.name}{devices.0.vram_total}").unwrap();
+        assert_eq!(template.render(&gpu).unwrap(), "Navi 22");
+
+        assert_eq!(
+            Template::parse("{relase}").unwrap().render(&kernel),
+            Err(
+                "unknown placeholder {relase}; available fields: architecture, name, release"
+                    .into()
+            )
+        );
+        assert!(Template::parse("{release").is_err());
+        assert!(Template::parse("{}").is_err());
+        assert!(Template::parse("a } b").is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use super::{downsample, ColourDepth};
+
+/// Colours for the parts of each line, as SGR parameter strings such as
+/// `"1;34"`. Unset parts are printed in the terminal's default colour, and
+/// nothing is coloured when `enabled` is false. Colours beyond `depth` are
+/// replaced with their nearest match when painted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub enabled: bool,
+    pub depth: ColourDepth,
+    pub label: Option<String>,
+    pub value: Option<String>,
+    pub separator: Option<String>,
+    /// Label colours for specific modules, matched case-insensitively by key
+    pub modules: Vec<(String, String)>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            depth: ColourDepth::default(),
+            label: None,
+            value: None,
+            separator: None,
+            modules: Vec::new(),
+        }
+    }
+}
+
+impl Theme {
+    /// A theme that writes no escape codes at all
+    pub fn plain() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+
+    /// The label colour override for a module, if any
+    pub fn module_colour(&self, key: &str) -> Option<&str> {
+        self.modules
+            .iter()
+            .rev()
+            .find(|(module, _)| module.eq_ignore_ascii_case(key))
+            .map(|(_, colour)| colour.as_str())
+    }
+
+    /// Wraps `text` in `colour`, or returns it unchanged when there is no
+    /// colour, colour is disabled or the text is empty
+    pub fn paint(&self, colour: Option<&str>, text: &str) -> String {
+        match colour {
+            Some(c) if self.enabled && !text.is_empty() => {
+                format!("\x1b[{}m{text}\x1b[0m", downsample(c, self.depth))
+            }
+            _ => text.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn painting() {
+        let theme = Theme {
+            modules: vec![("gpu".into(), "32".into()), ("GPU".into(), "35".into())],
+            ..Theme::default()
+        };
+        assert_eq!(theme.paint(Some("1;34"), "CPU"), "\x1b[1;34mCPU\x1b[0m");
+        assert_eq!(theme.paint(None, "CPU"), "CPU");
+        assert_eq!(Theme::plain().paint(Some("1;34"), "CPU"), "CPU");
+        assert_eq!(theme.module_colour("Gpu"), Some("35"));
+        assert_eq!(theme.module_colour("CPU"), None);
+        let console = Theme {
+            depth: ColourDepth::Ansi16,
+            ..Theme::default()
+        };
+        assert_eq!(
+            console.paint(Some("0;38;2;126;186;228"), "OS"),
+            "\x1b[0;94mOS\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use std::mem;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+const ELLIPSIS: char = '…';
+
+/// Columns `s` occupies in a terminal. Wide characters (CJK, most emoji)
+/// count as two, combining marks as zero, and ANSI escape sequences are
+/// skipped.
+pub fn display_width(s: &str) -> usize {
+    strip_ansi(s).width()
+}
+
+/// Removes CSI (`ESC [ ... final`) and OSC (`ESC ] ... BEL` or `ESC \`)
+/// sequences, plus any other two-byte `ESC x` sequence.
+pub fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != ESC {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                // Parameters and intermediates run up to a final byte in @..=~.
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == BEL || (c == ESC && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Right-aligns `s` in a field `width` columns wide, measuring by display
+/// width rather than `char` count as `{:>width$}` does
+pub fn pad_left(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(s));
+    format!("{}{s}", " ".repeat(padding))
+}
+
+/// Left-aligns `s` in a field `width` columns wide, measuring by display width
+pub fn pad_right(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(s));
+    format!("{s}{}", " ".repeat(padding))
+}
+
+/// Cuts `s` down to `width` columns, ending in an ellipsis when anything was
+/// removed. Escape sequences are dropped from strings that need cutting.
+pub fn truncate(s: &str, width: usize) -> String {
+    if display_width(s) <= width {
+        return s.to_owned();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in strip_ansi(s).chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push(ELLIPSIS);
+    out
+}
+
+/// Breaks `s` into lines of at most `width` columns at spaces, splitting
+/// words longer than a whole line. Escape sequences are dropped from strings
+/// that need wrapping.
+pub fn wrap(s: &str, width: usize) -> Vec<String> {
+    if width == 0 || display_width(s) <= width {
+        return vec![s.to_owned()];
+    }
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut used = 0;
+    for word in strip_ansi(s).split(' ') {
+        if used > 0 && used + 1 + word.width() > width {
+            lines.push(mem::take(&mut current));
+            used = 0;
+        }
+        if used > 0 {
+            current.push(' ');
+            used += 1;
+        }
+        for c in word.chars() {
+            let w = c.width().unwrap_or(0);
+            if used > 0 && used + w > width {
+                lines.push(mem::take(&mut current));
+                used = 0;
+            }
+            current.push(c);
+            used += w;
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widths() {
+        assert_eq!(display_width("Memory"), 6);
+        assert_eq!(display_width("Mémoire"), 7);
+        // e followed by a combining acute accent
+        assert_eq!(display_width("Me\u{301}moire"), 7);
+        assert_eq!(display_width("メモリ"), 6);
+        assert_eq!(display_width("🔋 Battery"), 10);
+        assert_eq!(display_width("\x1b[1;34mCPU\x1b[0m"), 3);
+        assert_eq!(
+            display_width("\x1b]8;;https://example.com\x07link\x1b]8;;\x1b\\"),
+            4
+        );
+    }
+
+    #[test]
+    fn padding() {
+        assert_eq!(pad_left("内存", 6), "  内存");
+        assert_eq!(pad_left("Kernel", 4), "Kernel");
+        assert_eq!(pad_right("内存", 6), "内存  ");
+    }
+
+    #[test]
+    fn truncation() {
+        assert_eq!(truncate("AMD Radeon RX 7900 XTX", 12), "AMD Radeon …");
+        assert_eq!(truncate("メモリ容量", 6), "メモ…");
+        assert_eq!(truncate("short", 12), "short");
+    }
+
+    #[test]
+    fn wrapping() {
+        assert_eq!(
+            wrap("NVIDIA GeForce RTX 4090, AMD Radeon Graphics", 24),
+            vec!["NVIDIA GeForce RTX 4090,", "AMD Radeon Graphics"]
+        );
+        assert_eq!(wrap("aaaaaaaaaa", 4), vec!["aaaa", "aaaa", "aa"]);
+    }
+}
@@ -0,0 +1,73 @@
+use std::env;
+use std::fmt::Display;
+
+use nix::unistd::{getuid, User};
+use serde::{Deserialize, Serialize};
+
+use crate::fetch::{display_width, Theme};
+
+/// The `user@host` title drawn above the modules
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub user: String,
+    pub host: String,
+}
+
+impl Header {
+    /// Reads the user name from the passwd entry of the real uid, falling back
+    /// to `$USER`, and the hostname from the kernel. Returns `None` if either
+    /// is unavailable.
+    pub fn new() -> Option<Self> {
+        let user = User::from_uid(getuid())
+            .ok()
+            .flatten()
+            .map(|u| u.name)
+            .or_else(|| env::var("USER").ok())?;
+        let host = sys_info::hostname().ok()?;
+        Some(Self { user, host })
+    }
+
+    /// The title and a rule of `rule` as wide as it, coloured like labels
+    /// and separators
+    pub fn render(&self, theme: &Theme, rule: char) -> String {
+        let width = display_width(&self.to_string());
+        let separator = theme.separator.as_deref();
+        format!(
+            "{}{}{}\n{}",
+            theme.paint(theme.label.as_deref(), &self.user),
+            theme.paint(separator, "@"),
+            theme.paint(theme.label.as_deref(), &self.host),
+            theme.paint(separator, &rule.to_string().repeat(width))
+        )
+    }
+}
+
+impl Display for Header {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.user, self.host)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header() {
+        let header = Header {
+            user: "ユーザー".into(),
+            host: "morpheus".into(),
+        };
+        assert_eq!(
+            header.render(&Theme::plain(), '-'),
+            "ユーザー@morpheus\n-----------------"
+        );
+        let theme = Theme {
+            label: Some("1;34".into()),
+            ..Theme::default()
+        };
+        assert!(header
+            .render(&theme, '─')
+            .starts_with("\x1b[1;34mユーザー\x1b[0m@\x1b[1;34mmorpheus"));
+    }
+}
@@ -1,7 +1,10 @@
 pub mod args;
 pub mod colourblocks;
+pub mod config;
+pub mod dmi;
 mod error;
 pub mod fetch;
+pub mod header;
 pub mod machine;
 pub mod modules;
 
@@ -6,9 +6,12 @@ use std::path::PathBuf;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-use crate::colourblocks::colourblocks;
-use crate::fetch::{Array, ModuleRegistration, SEPARATOR};
-use crate::Result;
+use crate::args::{IconPosition, IconSet};
+use crate::colourblocks::Blocks;
+use crate::config::Config;
+use crate::fetch::{Array, Bars, Line, ModuleRegistration, Overflow, Style, Template, Theme};
+use crate::header::Header;
+use crate::{Error, Result};
 
 /// Controls which modules are loaded. Baseline is all modules unless `none` is
 /// set, then it is empty. `show` adds to the baseline; `hide` removes from it.
@@ -31,22 +34,110 @@ impl ModuleFilter {
     }
 }
 
+/// How lines are fitted to the terminal and coloured. `module_overflow`
+/// overrides `overflow` for individual modules, matched case-insensitively by
+/// key.
+#[derive(Default, Clone)]
+pub struct Layout {
+    pub width: Option<usize>,
+    pub overflow: Overflow,
+    pub module_overflow: Vec<(String, Overflow)>,
+    pub style: Style,
+    pub theme: Theme,
+    /// Usage bars for values that expose a ratio; `None` leaves them off
+    pub bars: Option<Bars>,
+    /// Formats replacing the built-in display of specific modules
+    pub templates: Vec<(String, Template)>,
+    /// Module icons drawn with the labels; `None` leaves them off
+    pub icons: Option<Icons>,
+    /// Character the `user@host` header is underlined with; `None` leaves
+    /// the header off
+    pub header: Option<char>,
+    pub blocks: Blocks,
+}
+
+/// Which icons to draw and where. `overrides` replace the icon of individual
+/// modules, matched case-insensitively by key.
+#[derive(Default, Clone)]
+pub struct Icons {
+    pub set: IconSet,
+    pub position: IconPosition,
+    pub overrides: Vec<(String, String)>,
+}
+
+impl Icons {
+    fn icon_for<'a>(&'a self, entry: &ModuleRegistration) -> Option<&'a str> {
+        let builtin = match self.set {
+            IconSet::Nerd => entry.icon,
+            IconSet::Ascii => entry.ascii_icon,
+        };
+        self.overrides
+            .iter()
+            .rev()
+            .find(|(module, _)| module.eq_ignore_ascii_case(entry.key))
+            .map(|(_, icon)| icon.as_str())
+            .or(builtin)
+    }
+
+    /// Puts the icon on the module's first line only; further lines, such as
+    /// Swap under Memory, keep their text labels.
+    fn apply(&self, entry: &ModuleRegistration, lines: &mut [Line]) {
+        let (Some(icon), Some(first)) = (self.icon_for(entry), lines.first_mut()) else {
+            return;
+        };
+        first.name = match self.position {
+            IconPosition::Before => format!("{icon} {}", first.name),
+            IconPosition::Replace => icon.to_owned(),
+        };
+    }
+}
+
+impl Layout {
+    fn overflow_for(&self, key: &str) -> Overflow {
+        self.module_overflow
+            .iter()
+            .rev()
+            .find(|(module, _)| module.eq_ignore_ascii_case(key))
+            .map_or(self.overflow, |(_, overflow)| *overflow)
+    }
+
+    fn template_for(&self, key: &str) -> Option<&Template> {
+        self.templates
+            .iter()
+            .rev()
+            .find(|(module, _)| module.eq_ignore_ascii_case(key))
+            .map(|(_, template)| template)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Machine {
     // flatten collapses the map's key-value pairs into the top-level JSON
     // object, giving {"OS": {...}, "CPU": {...}} rather than {"modules": {...}}.
     #[serde(flatten)]
     modules: IndexMap<String, serde_json::Value>,
+    // Saved with the modules so an imported machine keeps its own user@host.
+    #[serde(rename = "Header", default, skip_serializing_if = "Option::is_none")]
+    header: Option<Header>,
     // Not persisted; defaults to true so --input path still shows colour blocks.
     #[serde(skip, default = "Machine::default_colour_blocks")]
     pub colour_blocks: bool,
+    #[serde(skip)]
+    pub layout: Layout,
+    // Not persisted either; an imported machine is shown with the current
+    // options.
+    #[serde(skip)]
+    pub config: Config,
 }
 
 impl Default for Machine {
     fn default() -> Self {
         Self {
             modules: IndexMap::default(),
+            header: None,
             colour_blocks: true,
+            layout: Layout::default(),
+            config: Config::default(),
         }
     }
 }
@@ -56,7 +147,7 @@ impl Machine {
         true
     }
 
-    pub fn new(filter: &ModuleFilter) -> Self {
+    pub fn new(filter: &ModuleFilter, config: &Config) -> Self {
         // inventory::iter order is not guaranteed, so sort by priority to
         // preserve the intended display order across builds.
         let mut entries: Vec<&ModuleRegistration> =
@@ -78,12 +169,27 @@ impl Machine {
             modules: entries
                 .iter()
                 .filter(|e| filter.is_active(e.key))
-                .filter_map(|e| Some((e.key.to_string(), (e.load)()?)))
+                .filter_map(|e| Some((e.key.to_string(), (e.load)(config)?)))
                 .collect(),
+            header: None,
             colour_blocks: true,
+            layout: Layout::default(),
+            config: config.clone(),
         }
     }
 
+    /// Reads the `user@host` header. Only done when the header is shown, so
+    /// it is not saved with machines that do not use it.
+    pub fn load_header(&mut self) {
+        self.header = Header::new();
+    }
+
+    /// Whether there is a `user@host` header to show; a machine exported
+    /// without `--header` has none saved
+    pub fn has_header(&self) -> bool {
+        self.header.is_some()
+    }
+
     /// Read a machine from a json file
     ///
     /// # Errors
@@ -106,6 +212,43 @@ impl Machine {
         Ok(())
     }
 
+    /// Checks that every template names a known module and only uses fields
+    /// that module has. Modules that were not loaded can only be checked by
+    /// name.
+    ///
+    /// # Errors
+    /// Returns `Error::Template` for the first template that does not fit
+    pub fn check_templates(&self) -> Result<()> {
+        let mut entries: Vec<&ModuleRegistration> =
+            inventory::iter::<ModuleRegistration>().collect();
+        entries.sort_by_key(|e| e.priority);
+        let known: Vec<&str> = entries.iter().map(|e| e.key).collect();
+        for (module, template) in &self.layout.templates {
+            let error = |reason| Error::Template {
+                module: module.clone(),
+                reason,
+            };
+            let Some(key) = known.iter().find(|k| k.eq_ignore_ascii_case(module)) else {
+                return Err(error(format!(
+                    "unknown module (known: {})",
+                    known.join(", ")
+                )));
+            };
+            if let Some(value) = self.modules.get(*key) {
+                template.render(value).map_err(error)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The layout's theme, with the OS colour standing in for a label colour
+    /// the theme leaves unset
+    fn theme(&self) -> Theme {
+        let mut theme = self.layout.theme.clone();
+        theme.label = theme.label.or_else(|| self.colour());
+        theme
+    }
+
     fn colour(&self) -> Option<String> {
         inventory::iter::<ModuleRegistration>()
             .find_map(|e| self.modules.get(e.key).and_then(|v| (e.colour)(v)))
@@ -115,7 +258,10 @@ impl Machine {
 impl From<&Machine> for Array {
     fn from(value: &Machine) -> Self {
         let mut array = Array::new();
-        array.set_colour(value.colour());
+        array.set_theme(value.theme());
+        array.set_width(value.layout.width);
+        array.set_style(value.layout.style.clone());
+        array.set_bars(value.layout.bars.clone());
 
         let mut entries: Vec<&ModuleRegistration> =
             inventory::iter::<ModuleRegistration>().collect();
@@ -123,8 +269,36 @@ impl From<&Machine> for Array {
 
         for entry in entries {
             if let Some(val) = value.modules.get(entry.key) {
-                if let Some(lines) = (entry.display)(val.clone()) {
-                    array.push_multi(lines);
+                if let Some(mut lines) = (entry.display)(val.clone(), &value.config) {
+                    if let Some(template) = value.layout.template_for(entry.key) {
+                        match template.render(val) {
+                            // A template collapses the module to one line
+                            // labelled with the module name, rather than the
+                            // first line's label, e.g. an interface name.
+                            Ok(content) => {
+                                lines.truncate(1);
+                                if let Some(first) = lines.first_mut() {
+                                    entry.key.clone_into(&mut first.name);
+                                    first.content = content;
+                                }
+                            }
+                            // check_templates rejects these before display,
+                            // so this only guards against a changed machine.
+                            Err(reason) => eprintln!(
+                                "warning: template for module '{}' not applied: {reason}",
+                                entry.key
+                            ),
+                        }
+                    }
+                    if let Some(icons) = &value.layout.icons {
+                        icons.apply(entry, &mut lines);
+                    }
+                    let overflow = value.layout.overflow_for(entry.key);
+                    let colour = value.layout.theme.module_colour(entry.key);
+                    array.push_multi(lines.into_iter().map(|l| {
+                        l.with_overflow(overflow)
+                            .with_colour(colour.map(str::to_owned))
+                    }));
                 }
             }
         }
@@ -135,14 +309,77 @@ impl From<&Machine> for Array {
 impl Display for Machine {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let array = Array::from(self);
+        if let (Some(rule), Some(header)) = (self.layout.header, &self.header) {
+            writeln!(f, "{}", header.render(&self.theme(), rule))?;
+        }
         write!(f, "{array}")?;
-        if self.colour_blocks {
+        if self.colour_blocks && self.layout.theme.enabled {
             write!(
                 f,
                 "\n{}",
-                colourblocks(array.get_indent() + SEPARATOR.len(), 16, 8)
+                self.layout
+                    .blocks
+                    .render(array.content_column(), self.layout.theme.depth)
             )?;
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetch::display_width;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn template_label() {
+        let mut machine: Machine = serde_json::from_value(serde_json::json!({
+            "GPU": {"devices": [
+                {"name": "Raphael", "vram_total": null, "vram_used": null},
+                {"name": "Navi 22", "vram_total": null, "vram_used": null},
+            ]},
+        }))
+        .unwrap();
+        machine.layout.theme = Theme::plain();
+        machine.layout.templates =
+            vec![("gpu".into(), Template::parse("{devices.1.name}").unwrap())];
+        assert_eq!(Array::from(&machine).to_string(), "GPU: Navi 22");
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn icon_labels() {
+        let mut machine: Machine = serde_json::from_value(serde_json::json!({
+            "Kernel": {"architecture": "x86_64", "name": "Linux", "release": "6.18.31"},
+            "GPU": {"devices": [{"name": "Navi 22", "vram_total": null, "vram_used": null}]},
+        }))
+        .unwrap();
+        machine.layout.theme = Theme::plain();
+        let mut icons = |set, overrides: Vec<(String, String)>| {
+            machine.layout.icons = Some(Icons {
+                set,
+                position: IconPosition::Before,
+                overrides,
+            });
+            Array::from(&machine).to_string()
+        };
+
+        assert_eq!(
+            icons(IconSet::Nerd, Vec::new()),
+            "\u{f013} Kernel: Linux 6.18.31 x86_64\n   \u{f26c} GPU: Navi 22"
+        );
+        assert_eq!(
+            icons(IconSet::Ascii, Vec::new()),
+            "krn Kernel: Linux 6.18.31 x86_64\n   gpu GPU: Navi 22"
+        );
+
+        // A two-column emoji still lines the content up with the other labels.
+        let output = icons(IconSet::Ascii, vec![("kernel".into(), "\u{1f427}".into())]);
+        let columns: Vec<usize> = output
+            .lines()
+            .map(|l| display_width(&l[..l.find(": ").unwrap()]))
+            .collect();
+        assert_eq!(columns, [9, 9]);
+    }
+}
@@ -1,26 +1,183 @@
+use std::env;
+use std::error::Error as _;
+use std::io::IsTerminal;
+use std::process::ExitCode;
+
 use clap::Parser;
 
-use ironfetch::args::Args;
-use ironfetch::machine::{Machine, ModuleFilter};
-use ironfetch::Result;
+use ironfetch::args::{self, Args, ColorChoice, NetworkHide, SensorView};
+use ironfetch::colourblocks::Blocks;
+use ironfetch::config::{Config, LoadConfig, NetworkConfig};
+use ironfetch::fetch::{detect_depth, Align, Bars, ColourDepth, Overflow, Style, Template, Theme};
+use ironfetch::machine::{Icons, Layout, Machine, ModuleFilter};
+use ironfetch::modules::terminal;
+use ironfetch::{Error, Result};
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprint!("Error: {error}");
+            let mut source = error.source();
+            while let Some(cause) = source {
+                eprint!(": {cause}");
+                source = cause.source();
+            }
+            eprintln!();
+            ExitCode::FAILURE
+        }
+    }
+}
 
-fn main() -> Result<()> {
-    let mut machine;
+fn run() -> Result<()> {
     let args = Args::parse();
-    if let Some(path) = args.input {
-        machine = Machine::from_file(path)?;
+    let load_defaults = LoadConfig::default();
+    let config = Config {
+        sysroot: args.sysroot,
+        network: NetworkConfig {
+            hide_loopback: args.network_hide.contains(&NetworkHide::Loopback),
+            hide_virtual: args.network_hide.contains(&NetworkHide::Virtual),
+            hide_addresses: args.network_hide.contains(&NetworkHide::Addresses),
+        },
+        load: LoadConfig {
+            warning: args.load_warning.unwrap_or(load_defaults.warning),
+            critical: args.load_critical.unwrap_or(load_defaults.critical),
+        },
+        sensor_chips: args.sensors == SensorView::Chips,
+        dmi_placeholders: args.dmi_placeholder,
+    };
+    // Built first so a template that does not parse is reported before any
+    // module loads.
+    let layout = Layout {
+        width: args.width.or_else(|| {
+            std::io::stdout()
+                .is_terminal()
+                .then(terminal::window_size)
+                .flatten()
+                .map(|size| usize::from(size.columns))
+        }),
+        overflow: overflow(args.overflow),
+        module_overflow: args
+            .module_overflow
+            .into_iter()
+            .map(|(module, policy)| (module, overflow(policy)))
+            .collect(),
+        style: Style::new(align(args.align), args.separator),
+        theme: Theme {
+            enabled: colour_enabled(args.color),
+            depth: args.colour_depth.map_or_else(detect_depth, colour_depth),
+            label: args.label_colour,
+            value: args.value_colour,
+            separator: args.separator_colour,
+            modules: args.module_colour,
+        },
+        bars: args.bars.then(|| {
+            let defaults = Bars::default();
+            let (fill, empty) = args.bar_chars.unwrap_or((defaults.fill, defaults.empty));
+            Bars {
+                width: args.bar_width.unwrap_or(defaults.width),
+                fill,
+                empty,
+                warning: args.bar_warning.unwrap_or(defaults.warning),
+                critical: args.bar_critical.unwrap_or(defaults.critical),
+                colours: args.bar_colours.unwrap_or(defaults.colours),
+            }
+        }),
+        icons: args.icons.map(|set| Icons {
+            set,
+            position: args.icon_position,
+            overrides: args.icon,
+        }),
+        header: args.header,
+        blocks: {
+            let defaults = Blocks::default();
+            Blocks {
+                range: args.block_range.unwrap_or(defaults.range),
+                per_row: args.block_row.unwrap_or(defaults.per_row),
+                glyph: args.block_glyph,
+                width: args.block_width.unwrap_or(defaults.width),
+                align: args.block_align,
+            }
+        },
+        templates: parse_templates(args.format)?,
+    };
+    let output = args.output.filter(|_| args.input.is_none());
+    let mut machine = if let Some(path) = args.input {
+        let mut machine = Machine::from_file(path)?;
+        machine.config = config;
+        machine
     } else {
         let filter = ModuleFilter {
             none: args.none,
             show: args.show,
             hide: args.hide,
         };
-        machine = Machine::new(&filter);
+        let mut machine = Machine::new(&filter, &config);
         machine.colour_blocks = !args.no_colour_blocks;
-        if let Some(path) = args.output {
-            machine.to_file(path)?;
+        if args.header.is_some() {
+            machine.load_header();
         }
+        machine
+    };
+    machine.layout = layout;
+    if machine.layout.header.is_some() && !machine.has_header() {
+        eprintln!(
+            "warning: no user@host header to show; export the machine with --header to save one"
+        );
+    }
+    machine.check_templates()?;
+    if let Some(path) = output {
+        machine.to_file(path)?;
     }
     println!("{machine}");
     Ok(())
 }
+
+fn parse_templates(format: Vec<(String, String)>) -> Result<Vec<(String, Template)>> {
+    format
+        .into_iter()
+        .map(|(module, template)| match Template::parse(&template) {
+            Ok(template) => Ok((module, template)),
+            Err(reason) => Err(Error::Template { module, reason }),
+        })
+        .collect()
+}
+
+/// The fetch core does not depend on the command line, so its enums are
+/// mapped from their clap counterparts here.
+fn align(align: args::Align) -> Align {
+    match align {
+        args::Align::Right => Align::Right,
+        args::Align::Left => Align::Left,
+        args::Align::None => Align::None,
+        args::Align::Dots => Align::Dots,
+    }
+}
+
+fn overflow(overflow: args::Overflow) -> Overflow {
+    match overflow {
+        args::Overflow::Wrap => Overflow::Wrap,
+        args::Overflow::Truncate => Overflow::Truncate,
+        args::Overflow::None => Overflow::None,
+    }
+}
+
+fn colour_depth(depth: args::ColourDepth) -> ColourDepth {
+    match depth {
+        args::ColourDepth::TrueColour => ColourDepth::TrueColour,
+        args::ColourDepth::Ansi256 => ColourDepth::Ansi256,
+        args::ColourDepth::Ansi16 => ColourDepth::Ansi16,
+    }
+}
+
+/// `auto` follows <https://no-color.org>: any non-empty `NO_COLOR` turns
+/// colour off, as does output that is not a terminal.
+fn colour_enabled(choice: ColorChoice) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && std::io::stdout().is_terminal()
+        }
+    }
+}
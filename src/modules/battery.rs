@@ -4,10 +4,11 @@ use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use zbus::{proxy, zvariant::OwnedValue};
 
-use crate::{fetch::Fetch, Result};
+use crate::fetch::{Fetch, Line, Ratio};
+use crate::Result;
 
-#[derive(Clone, Serialize, Deserialize, Fetch, Display)]
-#[fetch(priority = 13)]
+#[fetch_derive::register_module(priority = 130, icon = "\u{f240}", ascii_icon = "bat")]
+#[derive(Clone, Serialize, Deserialize, Display)]
 #[display("{} at {:.0}%", state, percentage)]
 pub struct Battery {
     percentage: f64,
@@ -93,3 +94,13 @@ impl Battery {
         Ok(Some(Self { percentage, state }))
     }
 }
+
+impl Fetch for Battery {
+    fn name(&self) -> &'static str {
+        "Battery"
+    }
+
+    fn as_fetchlines(&self) -> Vec<Line> {
+        vec![Line::from(self.clone()).with_ratio(Some(Ratio::level(self.percentage / 100.0)))]
+    }
+}
@@ -10,7 +10,7 @@ use crate::fetch::Fetch;
 use crate::Result;
 
 #[derive(Serialize, Deserialize, Clone, Fetch)]
-#[fetch(name = "CPU", priority = 7)]
+#[fetch(name = "CPU", priority = 70, icon = "\u{f4bc}", ascii_icon = "cpu")]
 pub struct Cpu {
     cores: Vec<Core>,
 }
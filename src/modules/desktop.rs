@@ -0,0 +1,278 @@
+use std::env;
+
+use derive_more::Display;
+use procfs::process::{all_processes, Process};
+use serde::{Deserialize, Serialize};
+use zbus::{proxy, zvariant::OwnedObjectPath};
+
+use crate::fetch::Fetch;
+use crate::Result;
+
+/// `XDG_CURRENT_DESKTOP` entries and their display names.
+const DESKTOPS: [(&str, &str); 16] = [
+    ("GNOME", "GNOME"),
+    ("KDE", "KDE Plasma"),
+    ("XFCE", "Xfce"),
+    ("X-Cinnamon", "Cinnamon"),
+    ("Cinnamon", "Cinnamon"),
+    ("MATE", "MATE"),
+    ("LXQt", "LXQt"),
+    ("LXDE", "LXDE"),
+    ("Budgie", "Budgie"),
+    ("Budgie:GNOME", "Budgie"),
+    ("COSMIC", "COSMIC"),
+    ("Pantheon", "Pantheon"),
+    ("Unity", "Unity"),
+    ("Deepin", "Deepin"),
+    ("Enlightenment", "Enlightenment"),
+    ("Hyprland", "Hyprland"),
+];
+
+/// `comm` of known window managers and compositors and their display names.
+const WINDOW_MANAGERS: [(&str, &str); 26] = [
+    ("gnome-shell", "Mutter"),
+    ("kwin_wayland", "KWin"),
+    ("kwin_x11", "KWin"),
+    ("xfwm4", "Xfwm4"),
+    ("muffin", "Muffin"),
+    ("cinnamon", "Muffin"),
+    ("marco", "Marco"),
+    ("cosmic-comp", "cosmic-comp"),
+    ("sway", "sway"),
+    ("Hyprland", "Hyprland"),
+    ("niri", "niri"),
+    ("river", "river"),
+    ("labwc", "labwc"),
+    ("wayfire", "Wayfire"),
+    ("weston", "Weston"),
+    ("gamescope", "gamescope"),
+    ("i3", "i3"),
+    ("bspwm", "bspwm"),
+    ("awesome", "awesome"),
+    ("dwm", "dwm"),
+    ("openbox", "Openbox"),
+    ("fluxbox", "Fluxbox"),
+    ("herbstluftwm", "herbstluftwm"),
+    ("icewm", "IceWM"),
+    ("enlightenment", "Enlightenment"),
+    ("qtile", "Qtile"),
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Display)]
+pub enum SessionType {
+    Wayland,
+    X11,
+    #[display("TTY")]
+    Tty,
+}
+
+impl SessionType {
+    /// Parses the values used by `XDG_SESSION_TYPE` and logind
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "wayland" => Some(Self::Wayland),
+            "x11" => Some(Self::X11),
+            "tty" => Some(Self::Tty),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Fetch)]
+#[fetch(name = "Desktop", priority = 190, icon = "\u{f2d0}", ascii_icon = "de")]
+pub struct Desktop {
+    environment: Option<String>,
+    window_manager: Option<String>,
+    session_type: Option<SessionType>,
+}
+
+#[proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1/session/auto"
+)]
+trait Session {
+    #[zbus(property, name = "Type")]
+    fn session_type(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn desktop(&self) -> zbus::Result<String>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.login1.User",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1/user/self"
+)]
+trait User {
+    /// The user's graphical session, if they have one
+    #[zbus(property)]
+    fn display(&self) -> zbus::Result<(String, OwnedObjectPath)>;
+}
+
+/// Session details as reported by logind
+struct LogindSession {
+    session_type: Option<SessionType>,
+    desktop: Option<String>,
+}
+
+impl Desktop {
+    /// Returns the desktop environment, window manager and display server of
+    /// the current session
+    ///
+    /// # Errors
+    /// Never returns an error; returns None if nothing graphical is found
+    pub fn new() -> Result<Option<Self>> {
+        let logind = logind_session().ok();
+
+        let session_type = env_session_type().or_else(|| logind.as_ref()?.session_type);
+        let environment = ["XDG_CURRENT_DESKTOP", "DESKTOP_SESSION"]
+            .iter()
+            .filter_map(|v| env::var(v).ok())
+            .chain(logind.and_then(|l| l.desktop))
+            .find_map(|d| desktop_name(&d));
+        let window_manager = if session_type == Some(SessionType::Tty) {
+            None
+        } else {
+            find_window_manager()
+        };
+
+        let graphical = session_type.is_some_and(|t| t != SessionType::Tty);
+        if environment.is_none() && window_manager.is_none() && !graphical {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            environment,
+            window_manager,
+            session_type,
+        }))
+    }
+}
+
+fn env_session_type() -> Option<SessionType> {
+    if let Some(t) = env::var("XDG_SESSION_TYPE")
+        .ok()
+        .and_then(|t| SessionType::parse(&t))
+    {
+        return Some(t);
+    }
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        Some(SessionType::Wayland)
+    } else if env::var_os("DISPLAY").is_some() {
+        Some(SessionType::X11)
+    } else {
+        None
+    }
+}
+
+/// Reads the caller's session from logind. Over SSH that session is a TTY, so
+/// the user's graphical session is used instead when there is one.
+fn logind_session() -> Result<LogindSession> {
+    let connection = zbus::blocking::Connection::system()?;
+    let mut session = SessionProxyBlocking::new(&connection)?;
+    let mut session_type = SessionType::parse(&session.session_type()?);
+
+    if session_type.is_none_or(|t| t == SessionType::Tty) {
+        let user = UserProxyBlocking::new(&connection)?;
+        if let Ok((_, path)) = user.display() {
+            // logind uses "/" for "no graphical session".
+            if path.as_str() != "/" {
+                session = SessionProxyBlocking::builder(&connection)
+                    .path(path)?
+                    .build()?;
+                session_type = SessionType::parse(&session.session_type()?);
+            }
+        }
+    }
+
+    Ok(LogindSession {
+        session_type,
+        desktop: session.desktop().ok().filter(|d| !d.is_empty()),
+    })
+}
+
+/// Picks a display name out of a colon-separated desktop list such as
+/// `ubuntu:GNOME`, preferring an entry that is a known desktop.
+fn desktop_name(value: &str) -> Option<String> {
+    let lookup = |entry: &str| {
+        DESKTOPS
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(entry))
+            .map(|(_, name)| (*name).to_owned())
+    };
+    lookup(value)
+        .or_else(|| value.split(':').find_map(lookup))
+        .or_else(|| value.split(':').find(|e| !e.is_empty()).map(str::to_owned))
+}
+
+/// Scans this user's processes for a known window manager or compositor
+fn find_window_manager() -> Option<String> {
+    let uid = Process::myself().ok()?.uid().ok()?;
+    all_processes()
+        .ok()?
+        .filter_map(std::result::Result::ok)
+        .filter(|p| p.uid().is_ok_and(|u| u == uid))
+        .filter_map(|p| p.stat().ok())
+        .find_map(|stat| window_manager_name(&stat.comm))
+}
+
+fn window_manager_name(comm: &str) -> Option<String> {
+    // xmonad runs as a per-user compiled binary, e.g. xmonad-x86_64-linux.
+    if comm.starts_with("xmonad") {
+        return Some("xmonad".to_owned());
+    }
+    WINDOW_MANAGERS
+        .iter()
+        .find(|(c, _)| *c == comm)
+        .map(|(_, name)| (*name).to_owned())
+}
+
+impl std::fmt::Display for Desktop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Tiling compositors often set XDG_CURRENT_DESKTOP to their own name.
+        let window_manager = self
+            .window_manager
+            .as_ref()
+            .filter(|wm| self.environment.as_ref() != Some(*wm));
+        let details: Vec<String> = window_manager
+            .cloned()
+            .into_iter()
+            .chain(self.session_type.map(|t| t.to_string()))
+            .collect();
+
+        match &self.environment {
+            Some(environment) if details.is_empty() => write!(f, "{environment}"),
+            Some(environment) => write!(f, "{environment} ({})", details.join(", ")),
+            None => write!(f, "{}", details.join(", ")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn desktop_names() {
+        assert_eq!(desktop_name("ubuntu:GNOME").as_deref(), Some("GNOME"));
+        assert_eq!(desktop_name("KDE").as_deref(), Some("KDE Plasma"));
+        assert_eq!(desktop_name("sway").as_deref(), Some("sway"));
+    }
+
+    #[test]
+    fn desktop_display() {
+        let desktop = Desktop {
+            environment: Some("GNOME".into()),
+            window_manager: Some("Mutter".into()),
+            session_type: Some(SessionType::Wayland),
+        };
+        assert_eq!(desktop.to_string(), "GNOME (Mutter, Wayland)");
+
+        let desktop = Desktop {
+            environment: Some("sway".into()),
+            window_manager: Some("sway".into()),
+            session_type: Some(SessionType::Wayland),
+        };
+        assert_eq!(desktop.to_string(), "sway (Wayland)");
+    }
+}
@@ -6,7 +6,7 @@ use zbus::zvariant::OwnedObjectPath;
 use crate::{fetch::Fetch, Result, GIGABYTE, TERABYTE};
 
 #[derive(Serialize, Deserialize, Clone, Fetch)]
-#[fetch(priority = 12)]
+#[fetch(priority = 120, icon = "\u{f0a0}", ascii_icon = "disk")]
 pub struct Disk {
     pub capacity: u64,
 }
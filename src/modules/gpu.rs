@@ -5,7 +5,7 @@ use std::path::Path;
 use serde::{Deserialize, Serialize};
 use udev::Enumerator;
 
-use crate::fetch::{Fetch, Line};
+use crate::fetch::{Fetch, Line, Ratio};
 use crate::{Result, GIBIBYTE};
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -15,7 +15,12 @@ pub struct GpuDevice {
     vram_used: Option<u64>,
 }
 
-#[fetch_derive::register_module(name = "GPU", priority = 11)]
+#[fetch_derive::register_module(
+    name = "GPU",
+    priority = 110,
+    icon = "\u{f26c}",
+    ascii_icon = "gpu"
+)]
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Gpu {
     devices: Vec<GpuDevice>,
@@ -95,17 +100,24 @@ impl Fetch for Gpu {
 
     fn as_fetchlines(&self) -> Vec<Line> {
         if self.devices.len() == 1 {
-            vec![("GPU", self.devices[0].to_string()).into()]
+            vec![Line::from(("GPU", &self.devices[0])).with_ratio(self.devices[0].vram_ratio())]
         } else {
             self.devices
                 .iter()
                 .enumerate()
-                .map(|(i, d)| (format!("GPU {}", i + 1), d.to_string()).into())
+                .map(|(i, d)| Line::from((format!("GPU {}", i + 1), d)).with_ratio(d.vram_ratio()))
                 .collect()
         }
     }
 }
 
+impl GpuDevice {
+    #[allow(clippy::cast_precision_loss)]
+    fn vram_ratio(&self) -> Option<Ratio> {
+        Ratio::used(self.vram_used? as f64, self.vram_total? as f64)
+    }
+}
+
 #[allow(clippy::cast_precision_loss)]
 impl std::fmt::Display for GpuDevice {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -6,7 +6,12 @@ use serde::{Deserialize, Serialize};
 use crate::{fetch::Fetch, Result};
 
 #[derive(Serialize, Deserialize, Clone, Fetch, Display)]
-#[fetch(name = "Hostname", priority = 5)]
+#[fetch(
+    name = "Hostname",
+    priority = 50,
+    icon = "\u{f0ac}",
+    ascii_icon = "host"
+)]
 pub struct HostName(pub String);
 
 impl HostName {
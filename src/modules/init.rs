@@ -0,0 +1,163 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use zbus::proxy;
+
+use crate::fetch::{Fetch, Line, Severity};
+use crate::Result;
+
+const INIT_COMM: &str = "/proc/1/comm";
+
+/// Runtime directories that identify the service manager when pid 1 is a
+/// generic `init`.
+const RUNTIME_DIRS: [(&str, &str); 4] = [
+    ("/run/openrc", "OpenRC"),
+    ("/run/runit", "runit"),
+    ("/run/s6", "s6"),
+    ("/run/dinit", "dinit"),
+];
+
+/// systemd states that mean something needs attention
+const UNHEALTHY_STATES: [&str; 2] = ["degraded", "maintenance"];
+
+#[fetch_derive::register_module(
+    name = "Init",
+    priority = 200,
+    icon = "\u{f011}",
+    ascii_icon = "init"
+)]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Init {
+    name: String,
+    version: Option<String>,
+    /// Overall system state, as reported by systemd
+    state: Option<String>,
+    failed_units: Option<u32>,
+}
+
+#[proxy(
+    interface = "org.freedesktop.systemd1.Manager",
+    default_service = "org.freedesktop.systemd1",
+    default_path = "/org/freedesktop/systemd1"
+)]
+trait Manager {
+    #[zbus(property)]
+    fn version(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn system_state(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "NFailedUnits")]
+    fn failed_units(&self) -> zbus::Result<u32>;
+}
+
+impl Init {
+    /// Returns the init system, with its state when it is systemd
+    ///
+    /// # Errors
+    /// Returns an error if the command name of pid 1 cannot be read
+    pub fn new() -> Result<Option<Self>> {
+        let comm = fs::read_to_string(INIT_COMM)?;
+        let Some(name) = init_name(comm.trim()) else {
+            return Ok(None);
+        };
+
+        let mut init = Self {
+            name,
+            version: None,
+            state: None,
+            failed_units: None,
+        };
+        if init.name == "systemd" {
+            // Leave the name on its own if the manager cannot be reached,
+            // e.g. inside a container without the system bus.
+            let _ = init.query_systemd();
+        }
+        Ok(Some(init))
+    }
+
+    fn query_systemd(&mut self) -> Result<()> {
+        let connection = zbus::blocking::Connection::system()?;
+        let manager = ManagerProxyBlocking::new(&connection)?;
+        self.version = parse_version(&manager.version()?);
+        self.state = Some(manager.system_state()?);
+        self.failed_units = Some(manager.failed_units()?);
+        Ok(())
+    }
+
+    fn severity(&self) -> Severity {
+        match &self.state {
+            Some(s) if UNHEALTHY_STATES.contains(&s.as_str()) => Severity::Critical,
+            _ => Severity::Normal,
+        }
+    }
+}
+
+fn init_name(comm: &str) -> Option<String> {
+    let name = match comm {
+        "systemd" => "systemd",
+        "openrc-init" => "OpenRC",
+        "runit" => "runit",
+        "s6-svscan" => "s6",
+        "dinit" => "dinit",
+        "init" => RUNTIME_DIRS
+            .iter()
+            .find(|(dir, _)| Path::new(dir).exists())
+            .map_or("SysV init", |(_, name)| name),
+        // Containers often run an application as pid 1.
+        _ => return None,
+    };
+    Some(name.to_owned())
+}
+
+/// Takes the leading number from versions like `257.5-1-arch`
+fn parse_version(version: &str) -> Option<String> {
+    let major: String = version
+        .trim_start_matches('v')
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    (!major.is_empty()).then_some(major)
+}
+
+impl std::fmt::Display for Init {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(version) = &self.version {
+            write!(f, " {version}")?;
+        }
+        match (&self.state, self.failed_units) {
+            (Some(state), Some(failed)) => write!(f, " ({state}, {failed} failed)"),
+            (Some(state), None) => write!(f, " ({state})"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Fetch for Init {
+    fn name(&self) -> &'static str {
+        "Init"
+    }
+
+    fn as_fetchlines(&self) -> Vec<Line> {
+        vec![Line::from(("Init", self)).with_severity(self.severity())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn init_display() {
+        let init = Init {
+            name: "systemd".into(),
+            version: parse_version("257.5-1-arch"),
+            state: Some("degraded".into()),
+            failed_units: Some(2),
+        };
+        assert_eq!(init.to_string(), "systemd 257 (degraded, 2 failed)");
+        assert_eq!(init.severity(), Severity::Critical);
+    }
+}
@@ -8,7 +8,7 @@ use crate::{fetch::Fetch, Error, Result};
 use derive_more::Display;
 
 #[derive(Serialize, Deserialize, Clone, Fetch, Display)]
-#[fetch(priority = 3)]
+#[fetch(priority = 30, icon = "\u{f013}", ascii_icon = "krn")]
 #[display("{} {} {}", name, release, architecture)]
 pub struct Kernel {
     release: String,
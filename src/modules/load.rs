@@ -0,0 +1,145 @@
+use procfs::prelude::*;
+use procfs::{KernelStats, LoadAverage};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, LoadConfig};
+use crate::fetch::{Fetch, Line, Severity};
+use crate::modules::cpu::Cpu;
+use crate::Result;
+
+#[fetch_derive::register_module(
+    name = "Load",
+    priority = 210,
+    config,
+    icon = "\u{f0e4}",
+    ascii_icon = "load"
+)]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Load {
+    one: f32,
+    five: f32,
+    fifteen: f32,
+    /// Processes currently runnable, from /proc/stat
+    running: Option<u32>,
+    /// Kernel scheduling entities (processes and threads) in existence
+    tasks: u32,
+    /// Logical cores, used to normalise the load
+    cores: Option<usize>,
+    /// Taken from the options in effect rather than saved with the module
+    #[serde(skip)]
+    thresholds: LoadConfig,
+}
+
+impl Load {
+    /// Returns the system load averages and process counts
+    ///
+    /// # Errors
+    /// Returns an error if /proc/loadavg cannot be read
+    pub fn new(config: &Config) -> Result<Option<Self>> {
+        let load = LoadAverage::current()?;
+        let running = KernelStats::current().ok().and_then(|s| s.procs_running);
+        let cores = Cpu::new()
+            .ok()
+            .flatten()
+            .map(|c| c.logical_core_count())
+            .filter(|c| *c > 0);
+        Ok(Some(Self {
+            one: load.one,
+            five: load.five,
+            fifteen: load.fifteen,
+            running,
+            tasks: load.max,
+            cores,
+            thresholds: config.load.clone(),
+        }))
+    }
+
+    /// One-minute load divided by the number of logical cores
+    #[allow(clippy::cast_precision_loss)]
+    fn per_core(&self) -> Option<f64> {
+        self.cores.map(|c| f64::from(self.one) / c as f64)
+    }
+
+    fn severity(&self) -> Severity {
+        match self.per_core() {
+            Some(l) if l >= self.thresholds.critical => Severity::Critical,
+            Some(l) if l >= self.thresholds.warning => Severity::Warning,
+            _ => Severity::Normal,
+        }
+    }
+}
+
+impl std::fmt::Display for Load {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.2}, {:.2}, {:.2}", self.one, self.five, self.fifteen)?;
+        if let Some(per_core) = self.per_core() {
+            write!(f, " ({per_core:.2}/core)")?;
+        }
+        match self.running {
+            Some(running) => write!(f, ", {running}/{} running", self.tasks),
+            None => write!(f, ", {} tasks", self.tasks),
+        }
+    }
+}
+
+impl Fetch for Load {
+    fn name(&self) -> &'static str {
+        "Load"
+    }
+
+    fn configure(&mut self, config: &Config) {
+        self.thresholds = config.load.clone();
+    }
+
+    fn as_fetchlines(&self) -> Vec<Line> {
+        vec![Line::from(("Load", self)).with_severity(self.severity())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_display() {
+        let load = Load {
+            one: 6.4,
+            five: 3.1,
+            fifteen: 1.05,
+            running: Some(3),
+            tasks: 1204,
+            cores: Some(8),
+            thresholds: LoadConfig::default(),
+        };
+        assert_eq!(
+            load.to_string(),
+            "6.40, 3.10, 1.05 (0.80/core), 3/1204 running"
+        );
+        assert_eq!(load.severity(), Severity::Warning);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn thresholds_from_config() {
+        let value = serde_json::json!({
+            "one": 6.4, "five": 3.1, "fifteen": 1.05,
+            "running": 3, "tasks": 1204, "cores": 8,
+            "thresholds": {"warning": 0.5, "critical": 0.6},
+        });
+        let config = Config {
+            load: LoadConfig {
+                warning: 0.9,
+                critical: 2.0,
+            },
+            ..Config::default()
+        };
+        let mut load: Load = serde_json::from_value(value).unwrap();
+        assert_eq!(load.thresholds, LoadConfig::default());
+        load.configure(&config);
+        assert_eq!(load.severity(), Severity::Normal);
+        assert!(serde_json::to_value(&load)
+            .unwrap()
+            .get("thresholds")
+            .is_none());
+    }
+}
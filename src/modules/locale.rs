@@ -0,0 +1,229 @@
+use std::env;
+use std::fs;
+
+use derive_more::Display;
+use nix::libc;
+use serde::{Deserialize, Serialize};
+use zbus::proxy;
+
+use crate::fetch::{Fetch, Line, Severity};
+use crate::Result;
+
+/// systemd's locale.conf, then Debian's equivalent
+const LOCALE_FILES: [&str; 2] = ["/etc/locale.conf", "/etc/default/locale"];
+const LOCALTIME: &str = "/etc/localtime";
+const TIMEZONE_FILE: &str = "/etc/timezone";
+
+/// Whether the system clock is kept in sync over NTP
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Display)]
+pub enum TimeSync {
+    #[display("NTP synced")]
+    Synchronized,
+    #[display("NTP not synced")]
+    Unsynchronized,
+    /// NTP is turned off in timedated
+    #[display("NTP off")]
+    Disabled,
+}
+
+#[fetch_derive::register_module(
+    name = "Locale",
+    priority = 230,
+    icon = "\u{f1ab}",
+    ascii_icon = "loc"
+)]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Locale {
+    /// Effective `LANG`-style locale name
+    lang: Option<String>,
+    timezone: Option<String>,
+    time_sync: Option<TimeSync>,
+}
+
+#[proxy(
+    interface = "org.freedesktop.timedate1",
+    default_service = "org.freedesktop.timedate1",
+    default_path = "/org/freedesktop/timedate1"
+)]
+trait Timedate {
+    #[zbus(property)]
+    fn timezone(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "NTP")]
+    fn ntp(&self) -> zbus::Result<bool>;
+
+    #[zbus(property, name = "NTPSynchronized")]
+    fn ntp_synchronized(&self) -> zbus::Result<bool>;
+}
+
+impl Locale {
+    /// Returns the locale, timezone and clock synchronisation status
+    ///
+    /// # Errors
+    /// Never returns an error; returns None if nothing could be read
+    pub fn new() -> Result<Option<Self>> {
+        let timedate = timedate().ok();
+        let lang = env_locale().or_else(file_locale);
+        let timezone = env_zone()
+            .or_else(localtime_zone)
+            .or_else(|| timedate.as_ref()?.timezone().ok())
+            .filter(|tz| !tz.is_empty());
+        let time_sync = timedate
+            .as_ref()
+            .and_then(timedate_sync)
+            .or_else(kernel_time_sync);
+
+        if lang.is_none() && timezone.is_none() && time_sync.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            lang,
+            timezone,
+            time_sync,
+        }))
+    }
+
+    fn severity(&self) -> Severity {
+        match self.time_sync {
+            Some(TimeSync::Unsynchronized) => Severity::Warning,
+            _ => Severity::Normal,
+        }
+    }
+}
+
+/// The locale in effect for this process, following the usual precedence
+fn env_locale() -> Option<String> {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|v| env::var(v).ok())
+        .find(|v| !v.is_empty())
+}
+
+fn file_locale() -> Option<String> {
+    LOCALE_FILES.iter().find_map(|path| {
+        let contents = fs::read_to_string(path).ok()?;
+        contents.lines().find_map(|l| {
+            let value = l.trim().strip_prefix("LANG=")?;
+            Some(value.trim_matches('"').to_owned())
+        })
+    })
+}
+
+/// The zone set by `TZ`, which overrides the system zone for this process
+fn env_zone() -> Option<String> {
+    zone_name(&env::var("TZ").ok()?)
+}
+
+/// Takes the zone name from the `/etc/localtime` symlink, e.g.
+/// `/usr/share/zoneinfo/Europe/London`, or from `/etc/timezone`
+fn localtime_zone() -> Option<String> {
+    if let Ok(target) = fs::read_link(LOCALTIME) {
+        let target = target.to_string_lossy();
+        if let Some((_, zone)) = target.split_once("zoneinfo/") {
+            return Some(zone.to_owned());
+        }
+    }
+    zone_name(&fs::read_to_string(TIMEZONE_FILE).ok()?)
+}
+
+/// Normalises a `TZ`-style value: `Europe/London`, `:Europe/London` and
+/// `:/usr/share/zoneinfo/Europe/London` all name `Europe/London`
+fn zone_name(tz: &str) -> Option<String> {
+    let tz = tz.trim().trim_start_matches(':');
+    let zone = tz.split_once("zoneinfo/").map_or(tz, |(_, zone)| zone);
+    (!zone.is_empty()).then(|| zone.to_owned())
+}
+
+fn timedate() -> Result<TimedateProxyBlocking<'static>> {
+    let connection = zbus::blocking::Connection::system()?;
+    Ok(TimedateProxyBlocking::new(&connection)?)
+}
+
+/// `NTPSynchronized` says whether the clock is synced; `NTP` whether a sync
+/// service is enabled at all
+fn timedate_sync(timedate: &TimedateProxyBlocking) -> Option<TimeSync> {
+    if timedate.ntp_synchronized().ok()? {
+        return Some(TimeSync::Synchronized);
+    }
+    Some(match timedate.ntp() {
+        Ok(false) => TimeSync::Disabled,
+        _ => TimeSync::Unsynchronized,
+    })
+}
+
+/// Asks the kernel whether the clock is synchronised, without changing it.
+/// This is a last resort for systems without timedated, e.g. without systemd
+/// or in a container with no system bus: `STA_UNSYNC` is only kept up to
+/// date by some NTP daemons, and cannot tell a disabled service from one
+/// that has not synced yet.
+fn kernel_time_sync() -> Option<TimeSync> {
+    // SAFETY: timex is plain data, and with `modes` zeroed adjtimex only
+    // reads the clock state into the struct.
+    let (state, timex) = unsafe {
+        let mut timex: libc::timex = std::mem::zeroed();
+        (libc::adjtimex(&raw mut timex), timex)
+    };
+    match state {
+        -1 => None,
+        libc::TIME_ERROR => Some(TimeSync::Unsynchronized),
+        _ if timex.status & libc::STA_UNSYNC != 0 => Some(TimeSync::Unsynchronized),
+        _ => Some(TimeSync::Synchronized),
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = [
+            self.lang.clone(),
+            self.timezone.clone(),
+            self.time_sync.map(|s| s.to_string()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+impl Fetch for Locale {
+    fn name(&self) -> &'static str {
+        "Locale"
+    }
+
+    fn as_fetchlines(&self) -> Vec<Line> {
+        vec![Line::from(("Locale", self)).with_severity(self.severity())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_display() {
+        let locale = Locale {
+            lang: Some("en_GB.UTF-8".into()),
+            timezone: Some("Europe/London".into()),
+            time_sync: Some(TimeSync::Unsynchronized),
+        };
+        assert_eq!(
+            locale.to_string(),
+            "en_GB.UTF-8, Europe/London, NTP not synced"
+        );
+        assert_eq!(locale.severity(), Severity::Warning);
+    }
+
+    #[test]
+    fn zone_names() {
+        assert_eq!(
+            zone_name("Europe/London\n").as_deref(),
+            Some("Europe/London")
+        );
+        assert_eq!(zone_name(":Asia/Tokyo").as_deref(), Some("Asia/Tokyo"));
+        assert_eq!(
+            zone_name(":/usr/share/zoneinfo/America/New_York").as_deref(),
+            Some("America/New_York")
+        );
+        assert_eq!(zone_name(":"), None);
+    }
+}
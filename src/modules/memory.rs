@@ -1,4 +1,4 @@
-use crate::fetch::{Fetch, Line};
+use crate::fetch::{Fetch, Line, Ratio};
 use crate::{Result, GIBIBYTE, KIBIBYTE};
 use derive_more::Display;
 use memdev::memory::Memory as MemoryDevices;
@@ -34,7 +34,7 @@ impl From<MemInfo> for MemStats {
     }
 }
 
-#[fetch_derive::register_module(priority = 8)]
+#[fetch_derive::register_module(priority = 80, icon = "\u{f2db}", ascii_icon = "mem")]
 #[derive(Serialize, Deserialize, Clone, Display)]
 #[display("{}", self.display())]
 pub struct Memory {
@@ -70,6 +70,16 @@ impl Memory {
         self.meminfo.swap_total - self.meminfo.swap_free
     }
 
+    #[allow(clippy::cast_precision_loss)]
+    pub fn ratio(&self) -> Option<Ratio> {
+        Ratio::used(self.used() as f64, self.meminfo.total as f64)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    pub fn swap_ratio(&self) -> Option<Ratio> {
+        Ratio::used(self.swap_used() as f64, self.meminfo.swap_total as f64)
+    }
+
     #[allow(clippy::cast_precision_loss)]
     pub fn display(&self) -> String {
         self.display_unit(
@@ -160,8 +170,8 @@ fn display_mem_unit(used: f64, total: f64, unit: &str) -> String {
 impl From<Memory> for Vec<Line> {
     fn from(value: Memory) -> Self {
         [
-            ("Memory", &value).into(),
-            ("Swap", value.display_swap()).into(),
+            Line::from(("Memory", &value)).with_ratio(value.ratio()),
+            Line::from(("Swap", value.display_swap())).with_ratio(value.swap_ratio()),
         ]
         .into()
     }
@@ -1,13 +1,24 @@
 pub mod battery;
 pub mod cpu;
+pub mod desktop;
 pub mod disk;
 pub mod gpu;
 pub mod hostname;
+pub mod init;
 pub mod kernel;
+pub mod load;
+pub mod locale;
 pub mod memory;
 pub mod model;
+pub mod network;
 pub mod osinfo;
+pub mod packages;
 pub mod platform;
 pub mod ppd;
+pub mod sensors;
 pub mod shell;
+pub mod terminal;
 pub mod uptime;
+pub mod users;
+pub mod virtualisation;
+pub mod wireless;
@@ -1,17 +1,75 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::fs;
+use std::path::Path;
 
-use crate::{fetch::Fetch, Result};
+use derive_more::Display;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone, Fetch)]
-#[fetch(priority = 4)]
+use crate::config::Config;
+use crate::dmi;
+use crate::fetch::{Fetch, Line};
+use crate::Result;
+
+const EFI_DIR: &str = "/sys/firmware/efi";
+/// `SecureBoot` variable under the EFI global variable GUID
+const SECURE_BOOT_VAR: &str =
+    "/sys/firmware/efi/efivars/SecureBoot-8be4df61-93ca-11d2-aa0d-00e098032b8c";
+
+/// Broad form factor, decoded from the SMBIOS chassis type
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Display)]
+pub enum Chassis {
+    Desktop,
+    Laptop,
+    Convertible,
+    Tablet,
+    #[display("All-in-one")]
+    AllInOne,
+    #[display("Mini PC")]
+    MiniPc,
+    Handheld,
+    Server,
+}
+
+impl Chassis {
+    /// Maps SMBIOS chassis type codes (DSP0134, table 17)
+    fn from_smbios(code: u8) -> Option<Self> {
+        match code {
+            3..=7 | 15 | 16 | 24 => Some(Self::Desktop),
+            8..=10 | 14 => Some(Self::Laptop),
+            11 => Some(Self::Handheld),
+            13 => Some(Self::AllInOne),
+            17 | 23 | 25 | 28 | 29 => Some(Self::Server),
+            30 => Some(Self::Tablet),
+            31 | 32 => Some(Self::Convertible),
+            35 | 36 => Some(Self::MiniPc),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Display)]
+pub enum BootMode {
+    #[display("UEFI")]
+    Uefi,
+    #[display("Legacy BIOS")]
+    Legacy,
+}
+
+#[fetch_derive::register_module(priority = 40, icon = "\u{f108}", ascii_icon = "mdl")]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Model {
     product_name: Option<String>,
-    board_vendor: String,
-    board_name: String,
+    board_vendor: Option<String>,
+    board_name: Option<String>,
+    sys_vendor: Option<String>,
+    product_version: Option<String>,
+    product_family: Option<String>,
+    bios_vendor: Option<String>,
+    bios_version: Option<String>,
+    /// ISO 8601 where the firmware reports a well-formed date
+    bios_date: Option<String>,
+    chassis: Option<Chassis>,
+    boot_mode: Option<BootMode>,
+    secure_boot: Option<bool>,
 }
 
 impl Model {
@@ -19,38 +77,233 @@ impl Model {
     ///
     /// # Errors
     ///
-    /// Returns io errors if information cannot be read
+    /// Never returns an error; missing DMI fields are left empty and the
+    /// module is skipped only if none can be read. Placeholder values are
+    /// kept here and dropped when the module is displayed.
     pub fn new() -> Result<Option<Self>> {
-        Ok(Some(Self {
-            product_name: match read_product_info("/sys/devices/virtual/dmi/id/product_name")?
-                .as_str()
-            {
-                "System Product Name" => None,
-                s => Some(s.to_owned()),
-            },
-            board_vendor: read_product_info("/sys/devices/virtual/dmi/id/board_vendor")?,
-            board_name: read_product_info("/sys/devices/virtual/dmi/id/board_name")?,
-        }))
+        let read = dmi::read_field;
+        let model = Self {
+            product_name: read("product_name"),
+            board_vendor: read("board_vendor"),
+            board_name: read("board_name"),
+            sys_vendor: read("sys_vendor"),
+            product_version: read("product_version"),
+            product_family: read("product_family"),
+            bios_vendor: read("bios_vendor"),
+            bios_version: read("bios_version"),
+            bios_date: read("bios_date").map(|d| iso_date(&d)),
+            chassis: read("chassis_type")
+                .and_then(|t| t.parse().ok())
+                .and_then(Chassis::from_smbios),
+            boot_mode: Some(if Path::new(EFI_DIR).exists() {
+                BootMode::Uefi
+            } else {
+                BootMode::Legacy
+            }),
+            secure_boot: secure_boot(),
+        };
+
+        if model.product_name.is_none()
+            && model.board_vendor.is_none()
+            && model.board_name.is_none()
+            && model.sys_vendor.is_none()
+        {
+            return Ok(None);
+        }
+        Ok(Some(model))
+    }
+
+    /// The firmware line, e.g. `American Megatrends 1.23 (2023-05-01), UEFI, Secure Boot on`
+    fn firmware(&self) -> Option<String> {
+        let bios: Vec<&str> = [&self.bios_vendor, &self.bios_version]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        let mut parts = Vec::new();
+        if !bios.is_empty() {
+            parts.push(match &self.bios_date {
+                Some(date) => format!("{} ({date})", bios.join(" ")),
+                None => bios.join(" "),
+            });
+        }
+        if let Some(mode) = self.boot_mode {
+            parts.push(mode.to_string());
+        }
+        if let Some(enabled) = self.secure_boot {
+            parts.push(format!(
+                "Secure Boot {}",
+                if enabled { "on" } else { "off" }
+            ));
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+
+    /// The board and product names, e.g. `ASUSTeK COMPUTER INC. PRIME X570-PRO`
+    fn board(&self) -> Option<String> {
+        let parts: Vec<&str> = [&self.board_vendor, &self.product_name, &self.board_name]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+
+    /// The system line, e.g. `LENOVO ThinkPad X1 Carbon Gen 9`
+    fn system(&self) -> Option<String> {
+        let version = self
+            .product_version
+            .as_ref()
+            .filter(|v| self.product_family.as_ref() != Some(*v));
+        let parts: Vec<&str> = [&self.sys_vendor, &self.product_family]
+            .into_iter()
+            .flatten()
+            .chain(version)
+            .map(String::as_str)
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+}
+
+/// Converts the DMI `MM/DD/YYYY` date format to `YYYY-MM-DD`
+fn iso_date(date: &str) -> String {
+    match date.split('/').collect::<Vec<_>>()[..] {
+        [month, day, year] if year.len() == 4 => format!("{year}-{month}-{day}"),
+        _ => date.to_owned(),
     }
 }
 
-fn read_product_info(path: &str) -> Result<String> {
-    let f = File::open(path)?;
-    let mut s = String::new();
-    BufReader::new(f).read_line(&mut s)?;
-    Ok(s.replace('\n', ""))
+/// The `SecureBoot` EFI variable is four bytes of attributes followed by the
+/// one-byte value.
+fn secure_boot() -> Option<bool> {
+    let var = fs::read(SECURE_BOOT_VAR).ok()?;
+    var.get(4).map(|v| *v == 1)
 }
 
+/// The board and product names, or the system vendor and product when the
+/// firmware leaves those unset, followed by the chassis
 impl std::fmt::Display for Model {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut text = String::new();
-        text.push_str(&self.board_vendor);
-        if let Some(v) = &self.product_name {
-            text.push(' ');
-            text.push_str(v);
-        }
-        text.push(' ');
-        text.push_str(&self.board_name);
-        write!(f, "{text}")
+        match (self.board().or_else(|| self.system()), self.chassis) {
+            (Some(name), Some(chassis)) => write!(f, "{name} ({chassis})"),
+            (Some(name), None) => write!(f, "{name}"),
+            (None, Some(chassis)) => write!(f, "{chassis}"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl Fetch for Model {
+    fn name(&self) -> &'static str {
+        "Model"
+    }
+
+    fn configure(&mut self, config: &Config) {
+        for field in [
+            &mut self.product_name,
+            &mut self.board_vendor,
+            &mut self.board_name,
+            &mut self.sys_vendor,
+            &mut self.product_version,
+            &mut self.product_family,
+            &mut self.bios_vendor,
+            &mut self.bios_version,
+            &mut self.bios_date,
+        ] {
+            dmi::clear_placeholder(field, config);
+        }
+    }
+
+    fn as_fetchlines(&self) -> Vec<Line> {
+        let mut lines = Vec::new();
+        // Every name may be a placeholder on a board with a blank DMI table.
+        if self.board().is_some() || self.system().is_some() || self.chassis.is_some() {
+            lines.push(("Model", self).into());
+        }
+        // Without board names the Model line already shows the system.
+        if let Some(system) = self.system().filter(|_| self.board().is_some()) {
+            lines.push(("System", system).into());
+        }
+        if let Some(firmware) = self.firmware() {
+            lines.push(("Firmware", firmware).into());
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_lines() {
+        let model = Model {
+            product_name: None,
+            board_vendor: Some("ASUSTeK COMPUTER INC.".into()),
+            board_name: Some("PRIME X570-PRO".into()),
+            sys_vendor: None,
+            product_version: None,
+            product_family: None,
+            bios_vendor: Some("American Megatrends Inc.".into()),
+            bios_version: Some("5003".into()),
+            bios_date: Some(iso_date("02/03/2023")),
+            chassis: Chassis::from_smbios(3),
+            boot_mode: Some(BootMode::Uefi),
+            secure_boot: Some(false),
+        };
+        assert_eq!(
+            model.to_string(),
+            "ASUSTeK COMPUTER INC. PRIME X570-PRO (Desktop)"
+        );
+        assert_eq!(
+            model.firmware().as_deref(),
+            Some("American Megatrends Inc. 5003 (2023-02-03), UEFI, Secure Boot off")
+        );
+    }
+
+    #[test]
+    fn system_fallback() {
+        let model = Model {
+            product_name: None,
+            board_vendor: None,
+            board_name: None,
+            sys_vendor: Some("LENOVO".into()),
+            product_version: Some("ThinkPad T14 Gen 3".into()),
+            product_family: None,
+            bios_vendor: None,
+            bios_version: None,
+            bios_date: None,
+            chassis: Chassis::from_smbios(10),
+            boot_mode: None,
+            secure_boot: None,
+        };
+        assert_eq!(model.to_string(), "LENOVO ThinkPad T14 Gen 3 (Laptop)");
+        assert_eq!(model.as_fetchlines().len(), 1);
+    }
+
+    #[test]
+    fn placeholders_dropped_on_display() {
+        let mut model = Model {
+            product_name: Some("To Be Filled By O.E.M.".into()),
+            board_vendor: Some("ASRock".into()),
+            board_name: Some("B450M Pro4".into()),
+            sys_vendor: Some("Default string".into()),
+            product_version: Some("Rev 1".into()),
+            product_family: None,
+            bios_vendor: None,
+            bios_version: None,
+            bios_date: None,
+            chassis: None,
+            boot_mode: None,
+            secure_boot: None,
+        };
+        let config = Config {
+            dmi_placeholders: vec!["Rev 1".into()],
+            ..Config::default()
+        };
+        model.configure(&config);
+        assert_eq!(model.to_string(), "ASRock B450M Pro4");
+        assert_eq!(model.system(), None);
+        assert_eq!(model.sys_vendor, None);
     }
 }
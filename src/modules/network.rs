@@ -0,0 +1,293 @@
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+use derive_more::Display;
+use nix::ifaddrs::getifaddrs;
+use nix::net::if_::InterfaceFlags;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::fetch::{Fetch, Line};
+use crate::Result;
+
+const NET_PATH: &str = "/sys/class/net";
+
+// ARPHRD_* values from linux/if_arp.h, as reported by /sys/class/net/*/type.
+const ARPHRD_ETHER: u32 = 1;
+const ARPHRD_LOOPBACK: u32 = 772;
+const ARPHRD_NONE: u32 = 65534;
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Display)]
+pub enum InterfaceKind {
+    #[display("Ethernet")]
+    Ethernet,
+    #[display("Wi-Fi")]
+    Wifi,
+    #[display("Bridge")]
+    Bridge,
+    #[display("VPN")]
+    Vpn,
+    #[display("Loopback")]
+    Loopback,
+    #[display("Virtual")]
+    Virtual,
+    #[display("Other")]
+    Other,
+}
+
+#[derive(Serialize, Deserialize, Clone, Display)]
+#[display("{address}/{prefix}")]
+pub struct Address {
+    address: IpAddr,
+    prefix: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Interface {
+    name: String,
+    kind: InterfaceKind,
+    #[serde(rename = "virtual")]
+    is_virtual: bool,
+    /// Link speed in Mb/s
+    speed: Option<u32>,
+    mac: Option<String>,
+    addresses: Vec<Address>,
+}
+
+#[fetch_derive::register_module(
+    name = "Network",
+    priority = 150,
+    icon = "\u{f0e8}",
+    ascii_icon = "net"
+)]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Network {
+    interfaces: Vec<Interface>,
+}
+
+impl Network {
+    /// Returns every network interface that is up, with all its addresses.
+    /// `--network-hide` is applied when the module is displayed, so a saved
+    /// machine can still be shown redacted.
+    ///
+    /// # Errors
+    /// Returns an error if the interface list or addresses cannot be read
+    pub fn new() -> Result<Option<Self>> {
+        let mut names: Vec<String> = fs::read_dir(NET_PATH)?
+            .filter_map(|e| e.ok()?.file_name().into_string().ok())
+            .collect();
+        names.sort();
+
+        let mut interfaces: Vec<Interface> = names
+            .iter()
+            .filter_map(|name| Interface::read(name, &Path::new(NET_PATH).join(name)))
+            .collect();
+
+        for ifaddr in getifaddrs()? {
+            let Some(interface) = interfaces
+                .iter_mut()
+                .find(|i| i.name == ifaddr.interface_name)
+            else {
+                continue;
+            };
+            let (Some(address), Some(netmask)) = (ifaddr.address, ifaddr.netmask) else {
+                continue;
+            };
+            if let (Some(a), Some(m)) = (address.as_sockaddr_in(), netmask.as_sockaddr_in()) {
+                interface.addresses.push(Address {
+                    address: IpAddr::V4(a.ip()),
+                    prefix: m.ip().to_bits().count_ones(),
+                });
+            } else if let (Some(a), Some(m)) =
+                (address.as_sockaddr_in6(), netmask.as_sockaddr_in6())
+            {
+                interface.addresses.push(Address {
+                    address: IpAddr::V6(a.ip()),
+                    prefix: m.ip().to_bits().count_ones(),
+                });
+            }
+        }
+
+        if interfaces.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self { interfaces }))
+    }
+}
+
+impl Interface {
+    /// Reads an interface from sysfs, returning None if it is down
+    fn read(name: &str, path: &Path) -> Option<Self> {
+        let flags = read_sysfs_string(&path.join("flags"))
+            .and_then(|v| i32::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+            .map_or_else(InterfaceFlags::empty, InterfaceFlags::from_bits_truncate);
+        // Loopback and tunnels report "unknown" rather than "up".
+        match read_sysfs_string(&path.join("operstate")).as_deref() {
+            Some("up") => {}
+            Some("unknown") if flags.contains(InterfaceFlags::IFF_UP) => {}
+            _ => return None,
+        }
+
+        let arp_type = read_sysfs_string(&path.join("type")).and_then(|v| v.parse().ok());
+        let devtype = read_sysfs_string(&path.join("uevent")).and_then(|v| {
+            v.lines()
+                .find_map(|l| l.strip_prefix("DEVTYPE=").map(ToOwned::to_owned))
+        });
+        let is_virtual =
+            fs::canonicalize(path).is_ok_and(|p| p.starts_with("/sys/devices/virtual"));
+
+        let kind = match (arp_type, devtype.as_deref()) {
+            (Some(ARPHRD_LOOPBACK), _) => InterfaceKind::Loopback,
+            (_, Some("wlan")) => InterfaceKind::Wifi,
+            _ if path.join("wireless").exists() => InterfaceKind::Wifi,
+            (_, Some("bridge")) => InterfaceKind::Bridge,
+            (_, Some("wireguard")) | (Some(ARPHRD_NONE), _) => InterfaceKind::Vpn,
+            _ if path.join("tun_flags").exists() => InterfaceKind::Vpn,
+            (Some(ARPHRD_ETHER), _) if is_virtual => InterfaceKind::Virtual,
+            (Some(ARPHRD_ETHER), _) => InterfaceKind::Ethernet,
+            _ => InterfaceKind::Other,
+        };
+
+        // Reading speed fails with EINVAL on links that do not report one; some
+        // drivers report -1 instead.
+        let speed = read_sysfs_string(&path.join("speed"))
+            .and_then(|v| v.parse::<i64>().ok())
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v > 0);
+
+        let mac = read_sysfs_string(&path.join("address"))
+            .filter(|v| !v.is_empty() && v != "00:00:00:00:00:00");
+
+        Some(Self {
+            name: name.to_owned(),
+            kind,
+            is_virtual,
+            speed,
+            mac,
+            addresses: Vec::new(),
+        })
+    }
+}
+
+fn read_sysfs_string(path: &Path) -> Option<String> {
+    Some(fs::read_to_string(path).ok()?.trim().to_owned())
+}
+
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = self
+            .interfaces
+            .iter()
+            .map(|i| format!("{} ({})", i.name, i.kind))
+            .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+impl std::fmt::Display for Interface {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(speed) = self.speed {
+            if speed >= 1000 && speed % 100 == 0 {
+                write!(f, " {} Gb/s", f64::from(speed) / 1000.0)?;
+            } else {
+                write!(f, " {speed} Mb/s")?;
+            }
+        }
+        if let Some(mac) = &self.mac {
+            write!(f, ", {mac}")?;
+        }
+        for address in &self.addresses {
+            write!(f, ", {address}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Fetch for Network {
+    fn name(&self) -> &'static str {
+        "Network"
+    }
+
+    fn configure(&mut self, config: &Config) {
+        let options = &config.network;
+        self.interfaces.retain(|i| {
+            let hidden = (options.hide_loopback && i.kind == InterfaceKind::Loopback)
+                || (options.hide_virtual && i.is_virtual);
+            !hidden
+        });
+        if options.hide_addresses {
+            for interface in &mut self.interfaces {
+                interface.mac = None;
+                interface.addresses.clear();
+            }
+        }
+    }
+
+    fn as_fetchlines(&self) -> Vec<Line> {
+        self.interfaces
+            .iter()
+            .map(|i| (&i.name, i).into())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interface(name: &str, kind: InterfaceKind, is_virtual: bool) -> Interface {
+        Interface {
+            name: name.into(),
+            kind,
+            is_virtual,
+            speed: None,
+            mac: Some("aa:bb:cc:dd:ee:ff".into()),
+            addresses: vec![Address {
+                address: IpAddr::V4([10, 0, 0, 2].into()),
+                prefix: 8,
+            }],
+        }
+    }
+
+    #[test]
+    fn hidden_details() {
+        let mut network = Network {
+            interfaces: vec![
+                interface("lo", InterfaceKind::Loopback, true),
+                interface("enp5s0", InterfaceKind::Ethernet, false),
+                interface("virbr0", InterfaceKind::Bridge, true),
+            ],
+        };
+        let mut config = Config::default();
+        config.network.hide_loopback = true;
+        config.network.hide_addresses = true;
+        network.configure(&config);
+        assert_eq!(network.to_string(), "enp5s0 (Ethernet), virbr0 (Bridge)");
+        assert_eq!(network.interfaces[0].to_string(), "Ethernet");
+
+        config.network.hide_virtual = true;
+        network.configure(&config);
+        assert_eq!(network.to_string(), "enp5s0 (Ethernet)");
+    }
+
+    #[test]
+    fn interface_display() {
+        let interface = Interface {
+            name: "enp5s0".into(),
+            kind: InterfaceKind::Ethernet,
+            is_virtual: false,
+            speed: Some(2500),
+            mac: Some("aa:bb:cc:dd:ee:ff".into()),
+            addresses: vec![Address {
+                address: IpAddr::V4([192, 168, 1, 20].into()),
+                prefix: 24,
+            }],
+        };
+        assert_eq!(
+            interface.to_string(),
+            "Ethernet 2.5 Gb/s, aa:bb:cc:dd:ee:ff, 192.168.1.20/24"
+        );
+    }
+}
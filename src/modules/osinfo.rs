@@ -5,7 +5,13 @@ use derive_more::Display;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Fetch, Display)]
-#[fetch(name = "OS", priority = 1, colour = "color")]
+#[fetch(
+    name = "OS",
+    priority = 10,
+    colour = "color",
+    icon = "\u{f17c}",
+    ascii_icon = "os"
+)]
 #[display("{} {} ({})", name, build_id, version_codename)]
 pub struct OsInfo {
     pub name: String,
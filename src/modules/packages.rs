@@ -0,0 +1,333 @@
+mod sqlite;
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::fetch::Fetch;
+use crate::Result;
+
+use sqlite::{Database, Value};
+
+const DPKG_STATUS: &str = "/var/lib/dpkg/status";
+const RPM_DATABASES: [&str; 2] = [
+    "/usr/lib/sysimage/rpm/rpmdb.sqlite",
+    "/var/lib/rpm/rpmdb.sqlite",
+];
+const PACMAN_LOCAL: &str = "/var/lib/pacman/local";
+const APK_INSTALLED: &str = "/lib/apk/db/installed";
+const FLATPAK_SYSTEM: &str = "/var/lib/flatpak";
+const FLATPAK_USER: &str = ".local/share/flatpak";
+const SNAP_MOUNTS: &str = "/snap";
+const NIX_DB: &str = "/nix/var/nix/db/db.sqlite";
+const NIX_PROFILES: &str = "/nix/var/nix/profiles";
+const NIX_USER_PROFILES: &str = ".local/state/nix/profiles";
+const PASSWD: &str = "/etc/passwd";
+
+/// Profile symlinks chain through a few generations at most; this bounds loops.
+const MAX_LINKS: usize = 16;
+
+#[derive(Serialize, Deserialize, Clone, Display)]
+#[display("{count} ({manager})")]
+pub struct PackageCount {
+    manager: String,
+    count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Fetch)]
+#[fetch(
+    name = "Packages",
+    priority = 170,
+    config,
+    icon = "\u{f487}",
+    ascii_icon = "pkg"
+)]
+pub struct Packages {
+    counts: Vec<PackageCount>,
+}
+
+impl Packages {
+    /// Counts installed packages for every package manager found under the sysroot
+    ///
+    /// # Errors
+    /// Never returns an error; package managers that cannot be read are skipped
+    pub fn new(config: &Config) -> Result<Option<Self>> {
+        let sources: [(&str, Option<u64>); 6] = [
+            ("dpkg", count_dpkg(config)),
+            ("rpm", count_rpm(config)),
+            ("pacman", count_pacman(config)),
+            ("apk", count_apk(config)),
+            ("flatpak", count_flatpak(config)),
+            ("snap", count_snap(config)),
+        ];
+
+        let mut counts: Vec<PackageCount> = sources
+            .into_iter()
+            .filter_map(|(manager, count)| {
+                Some(PackageCount {
+                    manager: manager.to_owned(),
+                    count: count.filter(|c| *c > 0)?,
+                })
+            })
+            .collect();
+        counts.extend(count_nix(config));
+
+        if counts.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self { counts }))
+    }
+}
+
+fn count_dpkg(config: &Config) -> Option<u64> {
+    let status = fs::read_to_string(config.sysroot_path(DPKG_STATUS)).ok()?;
+    Some(count_dpkg_installed(&status))
+}
+
+/// Counts stanzas whose status marks the package as installed, skipping
+/// removed packages that only have config files left.
+fn count_dpkg_installed(status: &str) -> u64 {
+    status
+        .lines()
+        .filter_map(|l| l.strip_prefix("Status: "))
+        .filter(|s| s.ends_with(" installed"))
+        .count() as u64
+}
+
+fn count_rpm(config: &Config) -> Option<u64> {
+    let db = RPM_DATABASES
+        .iter()
+        .find_map(|p| Database::open(&config.sysroot_path(p)).ok())?;
+    db.count_rows(db.table_root("Packages").ok()??).ok()
+}
+
+fn count_pacman(config: &Config) -> Option<u64> {
+    count_dirs(&config.sysroot_path(PACMAN_LOCAL))
+}
+
+fn count_apk(config: &Config) -> Option<u64> {
+    let installed = fs::read_to_string(config.sysroot_path(APK_INSTALLED)).ok()?;
+    Some(installed.lines().filter(|l| l.starts_with("P:")).count() as u64)
+}
+
+fn count_flatpak(config: &Config) -> Option<u64> {
+    let mut roots = vec![config.sysroot_path(FLATPAK_SYSTEM)];
+    if let Some(home) = home_dir(config) {
+        roots.push(config.sysroot_path(home.join(FLATPAK_USER)));
+    }
+    let counts: Vec<u64> = roots
+        .iter()
+        .flat_map(|root| [root.join("app"), root.join("runtime")])
+        .filter_map(|dir| count_dirs(&dir))
+        .collect();
+    (!counts.is_empty()).then(|| counts.iter().sum())
+}
+
+fn count_snap(config: &Config) -> Option<u64> {
+    let entries = fs::read_dir(config.sysroot_path(SNAP_MOUNTS)).ok()?;
+    // /snap/bin holds the command wrappers rather than a snap.
+    Some(
+        entries
+            .filter_map(std::result::Result::ok)
+            .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()) && e.file_name() != "bin")
+            .count() as u64,
+    )
+}
+
+/// Returns the closure size (number of store paths) of the system, user and
+/// home-manager profiles.
+fn count_nix(config: &Config) -> Vec<PackageCount> {
+    let home = home_dir(config);
+    let user = env::var("USER").ok();
+    let per_user = |name: &str| {
+        let mut candidates = Vec::new();
+        if let Some(user) = &user {
+            candidates.push(
+                Path::new(NIX_PROFILES)
+                    .join("per-user")
+                    .join(user)
+                    .join(name),
+            );
+        }
+        if let Some(home) = &home {
+            candidates.push(home.join(NIX_USER_PROFILES).join(name));
+        }
+        candidates
+    };
+
+    let mut user_profile = per_user("profile");
+    if let Some(home) = &home {
+        user_profile.push(home.join(".nix-profile"));
+    }
+    let profiles = [
+        ("nix-system", vec![Path::new(NIX_PROFILES).join("system")]),
+        ("nix-user", user_profile),
+        ("nix-hm", per_user("home-manager")),
+    ];
+
+    let resolved: Vec<(&str, String)> = profiles
+        .into_iter()
+        .filter_map(|(label, candidates)| {
+            let store_path = candidates.iter().find_map(|p| resolve_link(config, p))?;
+            Some((label, store_path.to_string_lossy().into_owned()))
+        })
+        .collect();
+    if resolved.is_empty() {
+        return Vec::new();
+    }
+
+    let Some(graph) = NixGraph::read(&config.sysroot_path(NIX_DB)) else {
+        return Vec::new();
+    };
+    resolved
+        .into_iter()
+        .filter_map(|(label, path)| {
+            Some(PackageCount {
+                manager: label.to_owned(),
+                count: graph.closure_size(&path)?,
+            })
+        })
+        .collect()
+}
+
+/// The Nix database, opened with the indexes used to walk a closure
+struct NixGraph {
+    db: Database,
+    /// `UNIQUE (path)` on `ValidPaths`: (path, id)
+    paths: u32,
+    /// `PRIMARY KEY (referrer, reference)` on `Refs`: (referrer, reference, rowid)
+    refs: u32,
+}
+
+impl NixGraph {
+    fn read(path: &Path) -> Option<Self> {
+        let db = Database::open(path).ok()?;
+        let paths = db.index_root("sqlite_autoindex_ValidPaths_1").ok()??;
+        let refs = db.index_root("sqlite_autoindex_Refs_1").ok()??;
+        Some(Self { db, paths, refs })
+    }
+
+    /// Counts the store paths reachable from `path`, reading only the index
+    /// pages for the paths in the closure rather than the whole database.
+    fn closure_size(&self, path: &str) -> Option<u64> {
+        let mut start = None;
+        self.db
+            .lookup(self.paths, &Value::Text(path.to_owned()), |values| {
+                if let Some(Value::Integer(id)) = values.last() {
+                    start = Some(*id);
+                }
+            })
+            .ok()?;
+        let start = start?;
+
+        let mut seen = HashSet::from([start]);
+        let mut queue = vec![start];
+        while let Some(id) = queue.pop() {
+            self.db
+                .lookup(self.refs, &Value::Integer(id), |values| {
+                    if let Some(Value::Integer(reference)) = values.get(1) {
+                        if seen.insert(*reference) {
+                            queue.push(*reference);
+                        }
+                    }
+                })
+                .ok()?;
+        }
+        Some(seen.len() as u64)
+    }
+}
+
+/// Follows a chain of symlinks inside the sysroot, returning the final
+/// target as a path relative to that root (e.g. `/nix/store/...`).
+fn resolve_link(config: &Config, path: &Path) -> Option<PathBuf> {
+    let mut path = path.to_path_buf();
+    for _ in 0..MAX_LINKS {
+        match fs::read_link(config.sysroot_path(&path)) {
+            Ok(target) if target.is_absolute() => path = target,
+            Ok(target) => path = path.parent()?.join(target),
+            Err(_) => return config.sysroot_path(&path).exists().then_some(path),
+        }
+    }
+    None
+}
+
+fn count_dirs(path: &Path) -> Option<u64> {
+    let entries = fs::read_dir(path).ok()?;
+    Some(
+        entries
+            .filter_map(std::result::Result::ok)
+            .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+            .count() as u64,
+    )
+}
+
+/// The home directory of `$USER` on the target system. Under a sysroot it is
+/// looked up in the root's passwd file, as the host's `$HOME` may not exist
+/// there or may belong to someone else.
+fn home_dir(config: &Config) -> Option<PathBuf> {
+    if config.sysroot.is_none() {
+        return env::var_os("HOME").map(PathBuf::from);
+    }
+    let user = env::var("USER").ok()?;
+    let passwd = fs::read_to_string(config.sysroot_path(PASSWD)).ok()?;
+    passwd_home(&passwd, &user)
+}
+
+/// The home directory field of `user`'s passwd entry
+fn passwd_home(passwd: &str, user: &str) -> Option<PathBuf> {
+    passwd
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.first() == Some(&user))
+        .and_then(|fields| fields.get(5).map(PathBuf::from))
+}
+
+impl std::fmt::Display for Packages {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = self.counts.iter().map(ToString::to_string).collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packages_display() {
+        let packages = Packages {
+            counts: vec![
+                PackageCount {
+                    manager: "dpkg".into(),
+                    count: 2315,
+                },
+                PackageCount {
+                    manager: "nix-user".into(),
+                    count: 412,
+                },
+            ],
+        };
+        assert_eq!(packages.to_string(), "2315 (dpkg), 412 (nix-user)");
+    }
+
+    #[test]
+    fn dpkg_installed() {
+        let status = "Package: bash\nStatus: install ok installed\n\n\
+                      Package: old\nStatus: deinstall ok config-files\n\n\
+                      Package: zsh\nStatus: install ok installed\n";
+        assert_eq!(count_dpkg_installed(status), 2);
+    }
+
+    #[test]
+    fn passwd_homes() {
+        let passwd = "root:x:0:0::/root:/bin/bash\n\
+                      neo:x:1000:100:Thomas Anderson:/home/neo:/run/current-system/sw/bin/fish\n";
+        assert_eq!(passwd_home(passwd, "neo"), Some(PathBuf::from("/home/neo")));
+        assert_eq!(passwd_home(passwd, "trinity"), None);
+    }
+}
@@ -7,7 +7,12 @@ use crate::{fetch::Fetch, Result};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Fetch)]
-#[fetch(name = "Platform", priority = 9)]
+#[fetch(
+    name = "Platform",
+    priority = 90,
+    icon = "\u{f085}",
+    ascii_icon = "plat"
+)]
 pub struct Profile {
     current: String,
     choices: String,
@@ -3,7 +3,12 @@ use ppd::PpdProxyBlocking;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Fetch)]
-#[fetch(name = "Profile", priority = 10)]
+#[fetch(
+    name = "Profile",
+    priority = 100,
+    icon = "\u{f0e7}",
+    ascii_icon = "pwr"
+)]
 pub struct Ppd {
     current: String,
     choices: String,
@@ -0,0 +1,416 @@
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::fetch::{Fetch, Line, Severity};
+use crate::Result;
+
+const HWMON_PATH: &str = "/sys/class/hwmon";
+
+/// Degrees below the critical threshold at which a temperature turns to a warning.
+const TEMP_WARNING_MARGIN: f64 = 10.0;
+
+/// Degrees Celsius a max or crit threshold must fall within to be believed.
+/// Some acpitz and nct67xx chips report 0 for thresholds they do not have.
+const SANE_THRESHOLDS: RangeInclusive<f64> = 20.0..=150.0;
+
+/// Chip name and sensor label pairs picked out for the compact summary line.
+const SUMMARY_SENSORS: [(&str, &str, &str); 6] = [
+    ("k10temp", "Tctl", "CPU"),
+    ("k10temp", "Tdie", "CPU"),
+    ("coretemp", "Package id 0", "CPU"),
+    ("zenpower", "Tdie", "CPU"),
+    ("nvme", "Composite", "NVMe"),
+    ("amdgpu", "edge", "GPU"),
+];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Temperature {
+    label: String,
+    /// Degrees Celsius
+    input: f64,
+    max: Option<f64>,
+    crit: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Fan {
+    label: String,
+    rpm: u64,
+    min: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Chip {
+    name: String,
+    /// The hwmon directory the chip was read from, e.g. `hwmon3`
+    #[serde(default)]
+    hwmon: String,
+    temperatures: Vec<Temperature>,
+    fans: Vec<Fan>,
+}
+
+#[fetch_derive::register_module(
+    name = "Sensors",
+    priority = 140,
+    icon = "\u{f2c9}",
+    ascii_icon = "temp"
+)]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Sensors {
+    chips: Vec<Chip>,
+    /// List every chip under the summary line
+    #[serde(skip)]
+    list_chips: bool,
+}
+
+impl Sensors {
+    /// Returns temperature and fan readings from every hwmon chip
+    ///
+    /// # Errors
+    /// Returns an error if the hwmon class directory cannot be read
+    pub fn new() -> Result<Option<Self>> {
+        let mut paths: Vec<_> = fs::read_dir(HWMON_PATH)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .collect();
+        // hwmon10 would sort before hwmon2 as a plain string.
+        paths.sort_by_key(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.trim_start_matches("hwmon").parse::<u32>().ok())
+        });
+
+        let chips: Vec<Chip> = paths
+            .iter()
+            .filter_map(|p| Chip::read(p))
+            .filter(|c| !c.temperatures.is_empty() || !c.fans.is_empty())
+            .collect();
+
+        if chips.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            chips,
+            list_chips: false,
+        }))
+    }
+
+    fn summary(&self) -> Vec<(&'static str, &Temperature)> {
+        let mut found: Vec<(&'static str, &Temperature)> = Vec::new();
+        for (chip_name, label, short) in SUMMARY_SENSORS {
+            if found.iter().any(|(s, _)| *s == short) {
+                continue;
+            }
+            let temp = self
+                .chips
+                .iter()
+                .filter(|c| c.name == chip_name)
+                .flat_map(|c| c.temperatures.iter())
+                .find(|t| t.label == label);
+            if let Some(t) = temp {
+                found.push((short, t));
+            }
+        }
+        found
+    }
+
+    fn fastest_fan(&self) -> Option<&Fan> {
+        self.chips
+            .iter()
+            .flat_map(|c| c.fans.iter())
+            .max_by_key(|f| f.rpm)
+    }
+
+    fn severity(&self) -> Severity {
+        self.summary()
+            .iter()
+            .map(|(_, t)| t.severity())
+            .chain(self.fastest_fan().map(Fan::severity))
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl Chip {
+    fn read(path: &Path) -> Option<Self> {
+        let name = read_sysfs_string(&path.join("name"))?;
+        let hwmon = path.file_name()?.to_string_lossy().into_owned();
+        let mut temperatures = Vec::new();
+        let mut fans = Vec::new();
+
+        let mut entries: Vec<String> = fs::read_dir(path)
+            .ok()?
+            .filter_map(|e| e.ok()?.file_name().into_string().ok())
+            .collect();
+        entries.sort();
+
+        for entry in entries {
+            if let Some(prefix) = sensor_prefix(&entry, "temp") {
+                let Some(input) = read_sysfs_milli(&path.join(format!("{prefix}_input"))) else {
+                    continue;
+                };
+                temperatures.push(Temperature {
+                    label: read_sysfs_string(&path.join(format!("{prefix}_label")))
+                        .unwrap_or_else(|| prefix.to_owned()),
+                    input,
+                    max: read_sysfs_milli(&path.join(format!("{prefix}_max"))),
+                    crit: read_sysfs_milli(&path.join(format!("{prefix}_crit"))),
+                });
+            } else if let Some(prefix) = sensor_prefix(&entry, "fan") {
+                let Some(rpm) = read_sysfs_u64(&path.join(format!("{prefix}_input"))) else {
+                    continue;
+                };
+                fans.push(Fan {
+                    label: read_sysfs_string(&path.join(format!("{prefix}_label")))
+                        .unwrap_or_else(|| prefix.to_owned()),
+                    rpm,
+                    min: read_sysfs_u64(&path.join(format!("{prefix}_min"))),
+                });
+            }
+        }
+
+        Some(Self {
+            name,
+            hwmon,
+            temperatures,
+            fans,
+        })
+    }
+
+    /// The chip name, followed by its hwmon directory when another chip has
+    /// the same name, as with several `NVMe` drives
+    fn label(&self, chips: &[Self]) -> String {
+        let shared = chips.iter().filter(|c| c.name == self.name).count() > 1;
+        if shared && !self.hwmon.is_empty() {
+            format!("{} ({})", self.name, self.hwmon)
+        } else {
+            self.name.clone()
+        }
+    }
+
+    fn severity(&self) -> Severity {
+        self.temperatures
+            .iter()
+            .map(Temperature::severity)
+            .chain(self.fans.iter().map(Fan::severity))
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl Temperature {
+    fn max(&self) -> Option<f64> {
+        self.max.filter(|t| SANE_THRESHOLDS.contains(t))
+    }
+
+    fn crit(&self) -> Option<f64> {
+        self.crit.filter(|t| SANE_THRESHOLDS.contains(t))
+    }
+
+    fn severity(&self) -> Severity {
+        match (self.max(), self.crit()) {
+            (_, Some(crit)) if self.input >= crit => Severity::Critical,
+            (_, Some(crit)) if self.input >= crit - TEMP_WARNING_MARGIN => Severity::Warning,
+            (Some(max), _) if self.input >= max => Severity::Warning,
+            _ => Severity::Normal,
+        }
+    }
+}
+
+impl Fan {
+    fn severity(&self) -> Severity {
+        match self.min {
+            Some(min) if min > 0 && self.rpm < min => Severity::Warning,
+            _ => Severity::Normal,
+        }
+    }
+}
+
+/// Returns the sensor prefix (e.g. `temp1`) if `entry` is that sensor's `_input` file.
+fn sensor_prefix<'a>(entry: &'a str, kind: &str) -> Option<&'a str> {
+    let prefix = entry.strip_suffix("_input")?;
+    let index = prefix.strip_prefix(kind)?;
+    index.parse::<u32>().ok().map(|_| prefix)
+}
+
+fn read_sysfs_string(path: &Path) -> Option<String> {
+    Some(fs::read_to_string(path).ok()?.trim().to_owned())
+}
+
+fn read_sysfs_u64(path: &Path) -> Option<u64> {
+    read_sysfs_string(path)?.parse().ok()
+}
+
+/// hwmon reports temperatures in millidegrees Celsius.
+#[allow(clippy::cast_precision_loss)]
+fn read_sysfs_milli(path: &Path) -> Option<f64> {
+    read_sysfs_string(path)?
+        .parse::<i64>()
+        .ok()
+        .map(|v| v as f64 / 1000.0)
+}
+
+impl std::fmt::Display for Sensors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts: Vec<String> = self
+            .summary()
+            .iter()
+            .map(|(short, t)| format!("{short} {:.0}°C", t.input))
+            .collect();
+        if let Some(fan) = self.fastest_fan() {
+            parts.push(format!("Fan {} RPM", fan.rpm));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+impl std::fmt::Display for Chip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let temps = self.temperatures.iter().map(ToString::to_string);
+        let fans = self.fans.iter().map(ToString::to_string);
+        let parts: Vec<String> = temps.chain(fans).collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+impl std::fmt::Display for Temperature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:.1}°C", self.label, self.input)?;
+        if let Some(crit) = self.crit() {
+            write!(f, " (crit {crit:.0}°C)")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Fan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} RPM", self.label, self.rpm)
+    }
+}
+
+impl Fetch for Sensors {
+    fn name(&self) -> &'static str {
+        "Sensors"
+    }
+
+    fn configure(&mut self, config: &Config) {
+        self.list_chips = config.sensor_chips;
+    }
+
+    fn as_fetchlines(&self) -> Vec<Line> {
+        let mut lines = Vec::new();
+        let has_summary = !self.summary().is_empty() || self.fastest_fan().is_some();
+        if has_summary {
+            lines.push(Line::from(("Sensors", self)).with_severity(self.severity()));
+        }
+        // With nothing recognised for the summary, the chips stand in for it.
+        if self.list_chips || !has_summary {
+            for chip in &self.chips {
+                let label = chip.label(&self.chips);
+                lines.push(Line::from((label, chip)).with_severity(chip.severity()));
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp(label: &str, input: f64, crit: Option<f64>) -> Temperature {
+        Temperature {
+            label: label.into(),
+            input,
+            max: None,
+            crit,
+        }
+    }
+
+    #[test]
+    fn sensors_summary() {
+        let sensors = Sensors {
+            chips: vec![
+                Chip {
+                    name: "k10temp".into(),
+                    hwmon: "hwmon1".into(),
+                    temperatures: vec![temp("Tctl", 54.2, None), temp("Tccd1", 48.0, None)],
+                    fans: vec![],
+                },
+                Chip {
+                    name: "nvme".into(),
+                    hwmon: "hwmon2".into(),
+                    temperatures: vec![temp("Composite", 40.9, Some(84.8))],
+                    fans: vec![],
+                },
+                Chip {
+                    name: "nct6798".into(),
+                    hwmon: "hwmon3".into(),
+                    temperatures: vec![],
+                    fans: vec![Fan {
+                        label: "fan2".into(),
+                        rpm: 1204,
+                        min: None,
+                    }],
+                },
+            ],
+            list_chips: false,
+        };
+        assert_eq!(sensors.to_string(), "CPU 54°C, NVMe 41°C, Fan 1204 RPM");
+        assert_eq!(sensors.as_fetchlines().len(), 1);
+        assert_eq!(sensors.chips[1].to_string(), "Composite 40.9°C (crit 85°C)");
+    }
+
+    #[test]
+    fn colliding_chip_labels() {
+        let nvme = |hwmon: &str| Chip {
+            name: "nvme".into(),
+            hwmon: hwmon.into(),
+            temperatures: vec![temp("Composite", 40.0, None)],
+            fans: vec![],
+        };
+        let gpu = Chip {
+            name: "amdgpu".into(),
+            hwmon: "hwmon4".into(),
+            temperatures: vec![temp("edge", 50.0, None)],
+            fans: vec![],
+        };
+        let sensors = Sensors {
+            chips: vec![nvme("hwmon1"), nvme("hwmon2"), gpu],
+            list_chips: true,
+        };
+        let labels: Vec<String> = sensors
+            .as_fetchlines()
+            .into_iter()
+            .map(|l| l.name)
+            .collect();
+        assert_eq!(
+            labels,
+            ["Sensors", "nvme (hwmon1)", "nvme (hwmon2)", "amdgpu"]
+        );
+    }
+
+    #[test]
+    fn placeholder_thresholds() {
+        let acpitz = temp("temp1", 27.8, Some(0.0));
+        assert_eq!(acpitz.severity(), Severity::Normal);
+        assert_eq!(acpitz.to_string(), "temp1 27.8°C");
+    }
+
+    #[test]
+    fn temperature_severity() {
+        assert_eq!(temp("edge", 60.0, Some(100.0)).severity(), Severity::Normal);
+        assert_eq!(
+            temp("edge", 92.0, Some(100.0)).severity(),
+            Severity::Warning
+        );
+        assert_eq!(
+            temp("edge", 100.0, Some(100.0)).severity(),
+            Severity::Critical
+        );
+    }
+}
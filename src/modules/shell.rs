@@ -1,15 +1,47 @@
-use std::{env, path::PathBuf};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use std::{env, thread};
 
 use crate::{fetch::Fetch, Error, Result};
 use derive_more::Display;
+use procfs::process::Process;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone, Fetch, Display)]
-#[fetch(priority = 2)]
-#[display("{}", self.name().unwrap_or_default())]
+/// Shells recognised while walking up the process tree.
+const KNOWN_SHELLS: [&str; 10] = [
+    "bash", "zsh", "fish", "nu", "sh", "dash", "ksh", "tcsh", "elvish", "xonsh",
+];
+
+/// Shells that report their version with `--version`.
+const VERSION_FLAG_SHELLS: [&str; 4] = ["bash", "zsh", "fish", "nu"];
+
+const VERSION_TIMEOUT: Duration = Duration::from_millis(500);
+const VERSION_POLL: Duration = Duration::from_millis(10);
+
+/// Bounds the walk up the process tree in case of a ppid loop.
+const MAX_ANCESTORS: usize = 64;
+
+/// Where the shell was found
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Display)]
+pub enum ShellSource {
+    /// The nearest ancestor process that is a known shell
+    #[display("process")]
+    Process,
+    /// The login shell from `$SHELL`
+    #[default]
+    #[display("$SHELL")]
+    Environment,
+}
+
+#[derive(Serialize, Deserialize, Clone, Fetch)]
+#[fetch(priority = 20, icon = "\u{f489}", ascii_icon = "sh")]
 pub struct Shell {
     pub path: PathBuf,
     pub version: String,
+    #[serde(default)]
+    pub source: ShellSource,
 }
 
 impl Shell {
@@ -17,11 +49,18 @@ impl Shell {
     ///
     /// # Errors
     ///
-    /// Returns error if shell variable cannot be read
+    /// Returns error if no shell is found among the parent processes and the
+    /// shell variable cannot be read
     pub fn new() -> Result<Option<Self>> {
+        let (path, source) = match process_shell() {
+            Some(p) => (p, ShellSource::Process),
+            None => (PathBuf::from(env::var("SHELL")?), ShellSource::Environment),
+        };
+        let version = shell_version(&path).unwrap_or_default();
         Ok(Some(Self {
-            path: PathBuf::from(env::var("SHELL")?),
-            version: String::new(),
+            path,
+            version,
+            source,
         }))
     }
 
@@ -37,3 +76,122 @@ impl Shell {
         }
     }
 }
+
+/// Walks up from the parent process and returns the executable of the
+/// nearest ancestor that is a known shell
+fn process_shell() -> Option<PathBuf> {
+    // Another user's processes (e.g. sudo) have an unreadable exe; keep walking.
+    ancestors()
+        .filter_map(|p| p.exe().ok())
+        .find(|exe| is_known_shell(exe))
+}
+
+pub(crate) fn is_known_shell(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| KNOWN_SHELLS.contains(&n))
+}
+
+/// Iterates over the ancestors of this process, nearest first
+pub(crate) fn ancestors() -> impl Iterator<Item = Process> {
+    let mut current = Process::myself().ok().map(|p| p.pid);
+    std::iter::from_fn(move || {
+        let parent = Process::new(current?).ok()?.stat().ok()?.ppid;
+        if parent <= 0 {
+            current = None;
+            return None;
+        }
+        current = Some(parent);
+        Process::new(parent).ok()
+    })
+    .take(MAX_ANCESTORS)
+}
+
+/// Runs `<shell> --version`, killing it if it does not exit in time
+fn shell_version(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    if !VERSION_FLAG_SHELLS.contains(&name) {
+        return None;
+    }
+
+    let mut child = Command::new(path)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let start = Instant::now();
+    loop {
+        if child.try_wait().ok()?.is_some() {
+            break;
+        }
+        if start.elapsed() > VERSION_TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        thread::sleep(VERSION_POLL);
+    }
+
+    let mut output = String::new();
+    child.stdout.take()?.read_to_string(&mut output).ok()?;
+    parse_version(&output)
+}
+
+/// Picks the version number out of `--version` output, e.g.
+/// `GNU bash, version 5.2.37(1)-release` or `fish, version 3.7.1`
+fn parse_version(output: &str) -> Option<String> {
+    let word = output
+        .lines()
+        .next()?
+        .split_whitespace()
+        .find(|w| w.starts_with(|c: char| c.is_ascii_digit()))?;
+    let version: String = word
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    Some(version.trim_end_matches('.').to_owned())
+}
+
+impl std::fmt::Display for Shell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name().unwrap_or_default())?;
+        if !self.version.is_empty() {
+            write!(f, " {}", self.version)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_parsing() {
+        let cases = [
+            (
+                "GNU bash, version 5.3.9(1)-release (x86_64-pc-linux-gnu)\nCopyright",
+                "5.3.9",
+            ),
+            ("zsh 5.9 (x86_64-pc-linux-gnu)", "5.9"),
+            ("fish, version 3.7.1", "3.7.1"),
+            ("0.101.0", "0.101.0"),
+        ];
+        for (output, version) in cases {
+            assert_eq!(parse_version(output).as_deref(), Some(version));
+        }
+    }
+
+    #[test]
+    fn shell_display() {
+        let shell = Shell {
+            path: PathBuf::from("/usr/bin/bash"),
+            version: "5.3.9".into(),
+            source: ShellSource::Process,
+        };
+        assert_eq!(shell.to_string(), "bash 5.3.9");
+    }
+}
@@ -0,0 +1,345 @@
+use std::env;
+use std::fs::{self, File};
+use std::os::fd::{AsRawFd, RawFd};
+use std::path::PathBuf;
+
+use derive_more::Display;
+use nix::libc;
+use serde::{Deserialize, Serialize};
+
+use crate::fetch::{Fetch, Line};
+use crate::modules::shell::{ancestors, is_known_shell};
+use crate::Result;
+
+/// Processes that sit between the shell and the terminal without being either.
+const PASSTHROUGH: [&str; 6] = ["sudo", "doas", "su", "script", "ironfetch", "nix"];
+
+/// `comm` of known terminal processes (truncated to 15 bytes by the kernel)
+/// and their display names.
+const TERMINAL_PROCESSES: [(&str, &str); 20] = [
+    ("kitty", "kitty"),
+    ("alacritty", "Alacritty"),
+    ("foot", "foot"),
+    ("footclient", "foot"),
+    ("wezterm-gui", "WezTerm"),
+    ("ghostty", "Ghostty"),
+    ("konsole", "Konsole"),
+    ("gnome-terminal-", "GNOME Terminal"),
+    ("kgx", "GNOME Console"),
+    ("xfce4-terminal", "Xfce Terminal"),
+    ("tilix", "Tilix"),
+    ("terminator", "Terminator"),
+    ("xterm", "xterm"),
+    ("urxvt", "urxvt"),
+    ("st", "st"),
+    ("rio", "Rio"),
+    ("contour", "Contour"),
+    ("code", "VS Code"),
+    ("tmux: server", "tmux"),
+    ("screen", "screen"),
+];
+
+/// Environment variables set by specific terminals, checked when the process
+/// tree does not lead to one (e.g. over SSH).
+const TERMINAL_VARIABLES: [(&str, &str); 10] = [
+    ("KITTY_WINDOW_ID", "kitty"),
+    ("ALACRITTY_WINDOW_ID", "Alacritty"),
+    ("WEZTERM_PANE", "WezTerm"),
+    ("GHOSTTY_RESOURCES_DIR", "Ghostty"),
+    ("KONSOLE_VERSION", "Konsole"),
+    ("GNOME_TERMINAL_SCREEN", "GNOME Terminal"),
+    ("TILIX_ID", "Tilix"),
+    ("TERMINATOR_UUID", "Terminator"),
+    ("WT_SESSION", "Windows Terminal"),
+    ("TMUX", "tmux"),
+];
+
+#[derive(Serialize, Deserialize, Clone, Display)]
+#[display("{columns}x{rows}")]
+pub struct WindowSize {
+    pub columns: u16,
+    pub rows: u16,
+}
+
+#[fetch_derive::register_module(
+    name = "Terminal",
+    priority = 180,
+    icon = "\u{f120}",
+    ascii_icon = "term"
+)]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Terminal {
+    name: String,
+    size: Option<WindowSize>,
+    font: Option<String>,
+}
+
+impl Terminal {
+    /// Returns the terminal emulator this process is running in
+    ///
+    /// # Errors
+    /// Never returns an error; returns None if no terminal is detected
+    pub fn new() -> Result<Option<Self>> {
+        // $TERM names a terminfo entry (often xterm-256color), not the emulator.
+        let Some(name) = terminal_from_process().or_else(terminal_from_env) else {
+            return Ok(None);
+        };
+        let font = config_font(&name);
+        Ok(Some(Self {
+            name,
+            size: window_size(),
+            font,
+        }))
+    }
+}
+
+/// Skips past the shell (and wrappers like sudo) to the first ancestor that
+/// is a known terminal.
+fn terminal_from_process() -> Option<String> {
+    for process in ancestors() {
+        let Ok(stat) = process.stat() else {
+            continue;
+        };
+        if let Some((_, name)) = TERMINAL_PROCESSES.iter().find(|(c, _)| *c == stat.comm) {
+            return Some((*name).to_owned());
+        }
+        let is_shell = process.exe().is_ok_and(|e| is_known_shell(&e));
+        if !is_shell && !PASSTHROUGH.contains(&stat.comm.as_str()) {
+            return None;
+        }
+    }
+    None
+}
+
+fn terminal_from_env() -> Option<String> {
+    if let Some((_, name)) = TERMINAL_VARIABLES
+        .iter()
+        .find(|(var, _)| env::var_os(var).is_some())
+    {
+        return Some((*name).to_owned());
+    }
+    env::var("TERM_PROGRAM").ok().map(|p| match p.as_str() {
+        "vscode" => "VS Code".to_owned(),
+        _ => p,
+    })
+}
+
+nix::ioctl_read_bad!(tiocgwinsz, libc::TIOCGWINSZ, libc::winsize);
+
+/// Returns the size of the terminal on stdout, or the controlling terminal if
+/// stdout is not one.
+pub fn window_size() -> Option<WindowSize> {
+    let tty = File::open("/dev/tty").ok();
+    [
+        Some(std::io::stdout().as_raw_fd()),
+        tty.as_ref().map(AsRawFd::as_raw_fd),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(fd_window_size)
+}
+
+fn fd_window_size(fd: RawFd) -> Option<WindowSize> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: TIOCGWINSZ only writes a winsize to the pointer, which refers to
+    // a live, correctly sized struct; an invalid fd makes the call fail.
+    unsafe { tiocgwinsz(fd, &raw mut size) }.ok()?;
+    (size.ws_col > 0).then_some(WindowSize {
+        columns: size.ws_col,
+        rows: size.ws_row,
+    })
+}
+
+/// Extracts the font from the contents of a terminal's config file
+type FontParser = fn(&str) -> Option<String>;
+
+fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+}
+
+/// Reads the font from the terminal's own config file, for terminals with a
+/// plain-text config
+fn config_font(terminal: &str) -> Option<String> {
+    let config = config_dir()?;
+    let (paths, parse): (Vec<PathBuf>, FontParser) = match terminal {
+        "kitty" => (vec![config.join("kitty/kitty.conf")], kitty_font),
+        "Alacritty" => (
+            vec![config.join("alacritty/alacritty.toml")],
+            alacritty_font,
+        ),
+        "foot" => (vec![config.join("foot/foot.ini")], foot_font),
+        "Ghostty" => (vec![config.join("ghostty/config")], ghostty_font),
+        "WezTerm" => {
+            let mut paths = vec![config.join("wezterm/wezterm.lua")];
+            if let Some(home) = env::var_os("HOME") {
+                paths.push(PathBuf::from(home).join(".wezterm.lua"));
+            }
+            (paths, wezterm_font)
+        }
+        _ => return None,
+    };
+    paths
+        .iter()
+        .find_map(|p| fs::read_to_string(p).ok())
+        .and_then(|c| parse(&c))
+}
+
+fn with_size(family: String, size: Option<&str>) -> String {
+    match size {
+        Some(s) => format!("{family} {s}"),
+        None => family,
+    }
+}
+
+/// `font_family JetBrains Mono` and `font_size 11.0`
+fn kitty_font(config: &str) -> Option<String> {
+    let value = |key: &str| {
+        config.lines().rev().find_map(|l| {
+            let (k, v) = l.trim().split_once(char::is_whitespace)?;
+            (k == key).then(|| v.trim())
+        })
+    };
+    Some(with_size(
+        value("font_family")?.to_owned(),
+        value("font_size"),
+    ))
+}
+
+/// `family = "..."` under `[font.normal]` and `size = 11` under `[font]`
+fn alacritty_font(config: &str) -> Option<String> {
+    let mut section = "";
+    let mut family = None;
+    let mut size = None;
+    for line in config.lines().map(str::trim) {
+        if let Some(s) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            section = s.trim();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match (section, key.trim()) {
+            ("font.normal", "family") => family = Some(unquote(value)),
+            ("font", "size") => size = Some(value.trim()),
+            // normal = { family = "...", style = "..." }
+            ("font", "normal") => {
+                family = value
+                    .split_once("family")
+                    .and_then(|(_, rest)| rest.split_once('='))
+                    .map(|(_, rest)| unquote(rest.split(',').next().unwrap_or_default()));
+            }
+            _ => {}
+        }
+    }
+    Some(with_size(family?, size))
+}
+
+/// `font=JetBrains Mono:size=11` in the main section
+fn foot_font(config: &str) -> Option<String> {
+    let value = config
+        .lines()
+        .take_while(|l| !l.starts_with('[') || l.trim() == "[main]")
+        .find_map(|l| l.trim().strip_prefix("font="))?;
+    // Fallback fonts are comma-separated; the first is the primary.
+    let primary = value.split(',').next()?;
+    let mut parts = primary.split(':');
+    let family = parts.next()?.trim().to_owned();
+    let size = parts.find_map(|p| p.strip_prefix("size="));
+    Some(with_size(family, size))
+}
+
+/// `font-family = JetBrains Mono` and `font-size = 11`
+fn ghostty_font(config: &str) -> Option<String> {
+    let value = |key: &str| {
+        config.lines().find_map(|l| {
+            let (k, v) = l.split_once('=')?;
+            (k.trim() == key).then(|| unquote(v))
+        })
+    };
+    let size = value("font-size");
+    Some(with_size(value("font-family")?, size.as_deref()))
+}
+
+/// `wezterm.font("JetBrains Mono")` and `font_size = 11`
+fn wezterm_font(config: &str) -> Option<String> {
+    let (_, rest) = config.split_once("wezterm.font")?;
+    let rest = rest.trim_start_matches(['(', ' ', '{']);
+    let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let family = rest[1..].split(quote).next()?.to_owned();
+    let size = config.lines().find_map(|l| {
+        let (k, v) = l.split_once('=')?;
+        (k.trim().trim_start_matches("config.") == "font_size")
+            .then(|| v.trim().trim_end_matches(','))
+    });
+    Some(with_size(family, size))
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches(['"', '\'']).to_owned()
+}
+
+impl std::fmt::Display for Terminal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(size) = &self.size {
+            write!(f, " ({size})")?;
+        }
+        Ok(())
+    }
+}
+
+impl Fetch for Terminal {
+    fn name(&self) -> &'static str {
+        "Terminal"
+    }
+
+    fn as_fetchlines(&self) -> Vec<Line> {
+        let mut lines = vec![("Terminal", self).into()];
+        if let Some(font) = &self.font {
+            lines.push(("Terminal Font", font).into());
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terminal_display() {
+        let terminal = Terminal {
+            name: "kitty".into(),
+            size: Some(WindowSize {
+                columns: 120,
+                rows: 40,
+            }),
+            font: None,
+        };
+        assert_eq!(terminal.to_string(), "kitty (120x40)");
+    }
+
+    #[test]
+    fn config_fonts() {
+        assert_eq!(
+            kitty_font("# font_family Hack\nfont_family      JetBrains Mono\nfont_size 11.0\n")
+                .as_deref(),
+            Some("JetBrains Mono 11.0")
+        );
+        assert_eq!(
+            alacritty_font("[font]\nsize = 12\n\n[font.normal]\nfamily = \"Iosevka\"\n").as_deref(),
+            Some("Iosevka 12")
+        );
+        assert_eq!(
+            foot_font("font=Fira Code:size=10,Noto Color Emoji\n[colors]\n").as_deref(),
+            Some("Fira Code 10")
+        );
+    }
+}
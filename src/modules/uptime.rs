@@ -7,7 +7,7 @@ const SECONDS_HOUR: u64 = SECONDS_MIN * 60;
 const SECONDS_DAY: u64 = SECONDS_HOUR * 24;
 
 #[derive(Serialize, Deserialize, Clone, Fetch)]
-#[fetch(name = "Uptime", priority = 6)]
+#[fetch(name = "Uptime", priority = 60, icon = "\u{f017}", ascii_icon = "up")]
 pub struct Uptime(pub Duration);
 
 impl Uptime {
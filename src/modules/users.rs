@@ -0,0 +1,241 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use nix::libc;
+use serde::{Deserialize, Serialize};
+use zbus::{proxy, zvariant::OwnedObjectPath};
+
+use crate::fetch::Fetch;
+use crate::{Error, Result};
+
+const SECONDS_MIN: u64 = 60;
+const SECONDS_HOUR: u64 = SECONDS_MIN * 60;
+const SECONDS_DAY: u64 = SECONDS_HOUR * 24;
+
+#[derive(Serialize, Deserialize, Clone, Fetch)]
+#[fetch(name = "Users", priority = 220, icon = "\u{f0c0}", ascii_icon = "usr")]
+pub struct Users {
+    current: String,
+    user_count: usize,
+    session_count: usize,
+    /// Time since the current session started
+    session_age: Option<Duration>,
+}
+
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait Manager {
+    fn list_sessions(&self) -> zbus::Result<Vec<SessionEntry>>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1/session/auto"
+)]
+trait Session {
+    #[zbus(property)]
+    fn name(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn class(&self) -> zbus::Result<String>;
+
+    /// Microseconds since the epoch
+    #[zbus(property)]
+    fn timestamp(&self) -> zbus::Result<u64>;
+}
+
+/// Id, uid, user name, seat and object path of a session from `ListSessions`
+type SessionEntry = (String, u32, String, String, OwnedObjectPath);
+
+/// A login session from logind or utmp
+struct LoginSession {
+    user: String,
+    /// Seconds since the epoch
+    started: Option<u64>,
+}
+
+impl Users {
+    /// Returns the current user and who else is logged in, from logind or,
+    /// failing that, utmp
+    ///
+    /// # Errors
+    /// Returns an error if logind cannot be reached and utmp has no records
+    pub fn new() -> Result<Option<Self>> {
+        let (sessions, current) = match logind_sessions() {
+            Ok(found) => found,
+            Err(_) => utmp_sessions()?,
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let current_user = current.as_ref().map(|c| c.user.clone());
+        let Some(user) = current_user.or_else(|| env::var("USER").ok()) else {
+            return Ok(None);
+        };
+        let users: HashSet<&str> = sessions.iter().map(|s| s.user.as_str()).collect();
+
+        Ok(Some(Self {
+            current: user,
+            user_count: users.len(),
+            session_count: sessions.len(),
+            session_age: current
+                .and_then(|c| c.started)
+                .map(|started| Duration::from_secs(now.saturating_sub(started))),
+        }))
+    }
+}
+
+/// Lists user sessions from logind, along with the caller's own session
+fn logind_sessions() -> Result<(Vec<LoginSession>, Option<LoginSession>)> {
+    let connection = zbus::blocking::Connection::system()?;
+    let manager = ManagerProxyBlocking::new(&connection)?;
+
+    let session = |proxy: &SessionProxyBlocking| LoginSession {
+        user: proxy.name().unwrap_or_default(),
+        started: proxy
+            .timestamp()
+            .ok()
+            .filter(|t| *t > 0)
+            .map(|t| t / 1_000_000),
+    };
+
+    let mut sessions = Vec::new();
+    for (_, _, _, _, path) in manager.list_sessions()? {
+        let proxy = SessionProxyBlocking::builder(&connection)
+            .path(path)?
+            .build()?;
+        // Skip greeters and the per-user service manager sessions.
+        if proxy.class().is_ok_and(|c| c.starts_with("user")) {
+            sessions.push(session(&proxy));
+        }
+    }
+    let current = SessionProxyBlocking::new(&connection)
+        .ok()
+        .filter(|p| p.name().is_ok())
+        .map(|p| session(&p));
+    Ok((sessions, current))
+}
+
+/// Reads `USER_PROCESS` records from utmp through libc, which knows the
+/// record layout of the target, matching the caller's session by the
+/// terminal on stdin
+fn utmp_sessions() -> Result<(Vec<LoginSession>, Option<LoginSession>)> {
+    let mut records = Vec::new();
+    // SAFETY: getutxent returns null or a pointer to a record that stays
+    // valid until the next call, and each record is copied out before that.
+    unsafe {
+        libc::setutxent();
+        while let Some(record) = libc::getutxent().as_ref() {
+            records.push(*record);
+        }
+        libc::endutxent();
+    }
+    // musl has no utmp and always reports an empty database.
+    if records.is_empty() {
+        return Err(Error::IsNone);
+    }
+
+    let tty = fs::read_link("/proc/self/fd/0").ok();
+    let tty = tty
+        .as_ref()
+        .and_then(|t| t.to_str())
+        .and_then(|t| t.strip_prefix("/dev/"));
+
+    let mut sessions = Vec::new();
+    let mut current = None;
+    for record in records.iter().filter(|r| r.ut_type == libc::USER_PROCESS) {
+        let line = c_string(&record.ut_line);
+        // tv_sec is an i32 in 64-bit glibc's utmpx and a time_t elsewhere.
+        #[allow(clippy::useless_conversion)]
+        let started = u64::try_from(i64::from(record.ut_tv.tv_sec)).ok();
+        let session = LoginSession {
+            user: c_string(&record.ut_user),
+            started,
+        };
+        if tty == Some(line.as_str()) {
+            current = Some(LoginSession {
+                user: session.user.clone(),
+                started: session.started,
+            });
+        }
+        sessions.push(session);
+    }
+    Ok((sessions, current))
+}
+
+/// Reads a NUL-padded fixed-size field
+fn c_string(field: &[libc::c_char]) -> String {
+    let bytes: Vec<u8> = field
+        .iter()
+        .take_while(|c| **c != 0)
+        .map(|c| u8::from_ne_bytes(c.to_ne_bytes()))
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Formats a duration with its two largest units, e.g. `2d 4h` or `12m`
+fn short_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let units = [
+        (secs / SECONDS_DAY, "d"),
+        (secs % SECONDS_DAY / SECONDS_HOUR, "h"),
+        (secs % SECONDS_HOUR / SECONDS_MIN, "m"),
+    ];
+    let parts: Vec<String> = units
+        .iter()
+        .skip_while(|(n, _)| *n == 0)
+        .take(2)
+        .map(|(n, unit)| format!("{n}{unit}"))
+        .collect();
+    if parts.is_empty() {
+        "0m".to_owned()
+    } else {
+        parts.join(" ")
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{count} {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+impl std::fmt::Display for Users {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.current)?;
+        if let Some(age) = self.session_age {
+            write!(f, " ({})", short_duration(age))?;
+        }
+        write!(
+            f,
+            ", {}, {}",
+            plural(self.user_count, "user"),
+            plural(self.session_count, "session")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn users_display() {
+        let users = Users {
+            current: "alice".into(),
+            user_count: 3,
+            session_count: 1,
+            session_age: Some(Duration::from_secs(2 * SECONDS_DAY + 4 * SECONDS_HOUR + 59)),
+        };
+        assert_eq!(users.to_string(), "alice (2d 4h), 3 users, 1 session");
+    }
+}
@@ -0,0 +1,175 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::dmi;
+use crate::{fetch::Fetch, Result};
+
+const HYPERVISOR_TYPE: &str = "/sys/hypervisor/type";
+const CPUINFO: &str = "/proc/cpuinfo";
+const INIT_CGROUP: &str = "/proc/1/cgroup";
+const KERNEL_OSRELEASE: &str = "/proc/sys/kernel/osrelease";
+/// Written by systemd-nspawn and other container managers following the
+/// systemd container interface
+const SYSTEMD_CONTAINER: &str = "/run/systemd/container";
+
+/// Substrings of DMI vendor or product strings and the hypervisor they identify.
+/// KVM is listed before QEMU since KVM guests report both.
+const DMI_HYPERVISORS: [(&str, &str); 12] = [
+    ("KVM", "KVM"),
+    ("Amazon EC2", "KVM"),
+    ("Google Compute Engine", "KVM"),
+    ("QEMU", "QEMU"),
+    ("VMware", "VMware"),
+    ("VirtualBox", "VirtualBox"),
+    ("innotek", "VirtualBox"),
+    ("Xen", "Xen"),
+    ("Parallels", "Parallels"),
+    ("Bochs", "Bochs"),
+    ("BHYVE", "bhyve"),
+    ("Microsoft Corporation Virtual Machine", "Hyper-V"),
+];
+
+/// Substrings of `/proc/1/cgroup` and the container runtime they identify.
+const CGROUP_CONTAINERS: [(&str, &str); 4] = [
+    ("docker", "Docker"),
+    ("libpod", "Podman"),
+    ("kubepods", "Kubernetes"),
+    ("lxc", "LXC"),
+];
+
+#[derive(Serialize, Deserialize, Clone, Fetch)]
+#[fetch(
+    name = "Virtualisation",
+    priority = 45,
+    config,
+    icon = "\u{f1b3}",
+    ascii_icon = "vm"
+)]
+pub struct Virtualisation {
+    hypervisor: Option<String>,
+    container: Option<String>,
+}
+
+impl Virtualisation {
+    /// Detects the hypervisor and container runtime, if any
+    ///
+    /// # Errors
+    /// Never returns an error; returns None on bare metal outside a container
+    pub fn new(config: &Config) -> Result<Option<Self>> {
+        let hypervisor = dmi_hypervisor(config)
+            .or_else(sys_hypervisor)
+            .or_else(cpuid_hypervisor);
+        let container = container();
+        if hypervisor.is_none() && container.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            hypervisor,
+            container,
+        }))
+    }
+}
+
+fn read_trimmed<P: AsRef<Path>>(path: P) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim().to_owned())
+        .filter(|s| !s.is_empty())
+}
+
+fn dmi_hypervisor(config: &Config) -> Option<String> {
+    let dmi = ["sys_vendor", "product_name", "bios_vendor"]
+        .iter()
+        .filter_map(|field| dmi::read_field(field))
+        .filter(|value| !dmi::is_placeholder(value, config))
+        .collect::<Vec<_>>()
+        .join(" ");
+    match_table(&dmi, &DMI_HYPERVISORS)
+}
+
+fn sys_hypervisor() -> Option<String> {
+    read_trimmed(HYPERVISOR_TYPE).map(|t| match t.as_str() {
+        "xen" => "Xen".to_owned(),
+        _ => t,
+    })
+}
+
+/// The CPUID hypervisor bit says we are a guest without saying of what.
+fn cpuid_hypervisor() -> Option<String> {
+    let cpuinfo = fs::read_to_string(CPUINFO).ok()?;
+    cpuinfo
+        .lines()
+        .find(|l| l.starts_with("flags"))?
+        .split_whitespace()
+        .any(|f| f == "hypervisor")
+        .then(|| "VM".to_owned())
+}
+
+fn container() -> Option<String> {
+    if Path::new("/.dockerenv").exists() {
+        return Some("Docker".to_owned());
+    }
+    if Path::new("/run/.containerenv").exists() {
+        return Some("Podman".to_owned());
+    }
+    if let Some(name) = read_trimmed(SYSTEMD_CONTAINER) {
+        return Some(container_name(&name));
+    }
+    if let Some(c) = fs::read_to_string(INIT_CGROUP)
+        .ok()
+        .and_then(|cgroup| match_table(&cgroup, &CGROUP_CONTAINERS))
+    {
+        return Some(c);
+    }
+    // WSL kernels are tagged in their release string, e.g. 5.15.90.1-microsoft-standard-WSL2.
+    read_trimmed(KERNEL_OSRELEASE)
+        .filter(|r| r.to_lowercase().contains("microsoft"))
+        .map(|_| "WSL".to_owned())
+}
+
+/// Maps the `container=` value used by the systemd container interface
+fn container_name(name: &str) -> String {
+    match name {
+        "systemd-nspawn" => "systemd-nspawn",
+        "docker" => "Docker",
+        "podman" => "Podman",
+        "lxc" | "lxc-libvirt" => "LXC",
+        "wsl" => "WSL",
+        other => other,
+    }
+    .to_owned()
+}
+
+fn match_table(haystack: &str, table: &[(&str, &str)]) -> Option<String> {
+    table
+        .iter()
+        .find(|(needle, _)| haystack.contains(needle))
+        .map(|(_, name)| (*name).to_owned())
+}
+
+impl std::fmt::Display for Virtualisation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.container, &self.hypervisor) {
+            (Some(container), Some(hypervisor)) => write!(f, "{container} on {hypervisor}"),
+            (Some(only), None) | (None, Some(only)) => write!(f, "{only}"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn virtualisation_display() {
+        let virt = Virtualisation {
+            hypervisor: match_table("QEMU Standard PC (Q35 + ICH9, 2009) KVM", &DMI_HYPERVISORS),
+            container: Some(container_name("docker")),
+        };
+        assert_eq!(virt.to_string(), "Docker on KVM");
+    }
+}
@@ -0,0 +1,321 @@
+mod nl80211;
+
+use std::fs;
+
+use nix::net::if_::if_nametoindex;
+use serde::{Deserialize, Serialize};
+use zbus::{proxy, zvariant::OwnedObjectPath};
+
+use crate::fetch::{Fetch, Line};
+use crate::Result;
+
+use nl80211::Nl80211;
+
+const PROC_WIRELESS: &str = "/proc/net/wireless";
+
+/// Link quality in /proc/net/wireless is out of 70 for cfg80211 drivers.
+const PROC_QUALITY_MAX: f64 = 70.0;
+
+/// `NM_DEVICE_TYPE_WIFI` from the `NetworkManager` D-Bus API
+const NM_DEVICE_TYPE_WIFI: u32 = 2;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WirelessLink {
+    interface: String,
+    ssid: String,
+    /// dBm
+    signal: Option<i32>,
+    /// Percent
+    quality: Option<u8>,
+    /// MHz
+    frequency: Option<u32>,
+    /// kbit/s
+    bitrate: Option<u32>,
+}
+
+#[fetch_derive::register_module(
+    name = "Wireless",
+    priority = 160,
+    icon = "\u{f1eb}",
+    ascii_icon = "wifi"
+)]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Wireless {
+    links: Vec<WirelessLink>,
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager"
+)]
+trait NetworkManager {
+    fn get_devices(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Device",
+    default_service = "org.freedesktop.NetworkManager",
+    assume_defaults = false
+)]
+trait NmDevice {
+    #[zbus(property)]
+    fn interface(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn device_type(&self) -> zbus::Result<u32>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Device.Wireless",
+    default_service = "org.freedesktop.NetworkManager",
+    assume_defaults = false
+)]
+trait NmWireless {
+    #[zbus(property)]
+    fn active_access_point(&self) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(property)]
+    fn bitrate(&self) -> zbus::Result<u32>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.AccessPoint",
+    default_service = "org.freedesktop.NetworkManager",
+    assume_defaults = false
+)]
+trait NmAccessPoint {
+    #[zbus(property)]
+    fn ssid(&self) -> zbus::Result<Vec<u8>>;
+
+    #[zbus(property)]
+    fn frequency(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn strength(&self) -> zbus::Result<u8>;
+}
+
+/// An interface listed in /proc/net/wireless
+struct ProcEntry {
+    interface: String,
+    quality: Option<u8>,
+    level: Option<i32>,
+}
+
+impl Wireless {
+    /// Returns connected wireless interfaces, read over nl80211 or, when that
+    /// finds none, from `NetworkManager`
+    ///
+    /// # Errors
+    /// Returns an error if nl80211 finds no links and `NetworkManager` cannot
+    /// be queried
+    pub fn new() -> Result<Option<Self>> {
+        let entries = read_proc_wireless().unwrap_or_default();
+
+        let nl_links = match Nl80211::new() {
+            Ok(mut nl) => entries
+                .iter()
+                .filter_map(|e| link_from_nl80211(&mut nl, e))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        let links = or_network_manager(nl_links, links_from_network_manager)?;
+
+        if links.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self { links }))
+    }
+}
+
+/// Keeps the nl80211 links unless there are none, e.g. because the socket
+/// could not be opened or the driver does not answer `NL80211_CMD_GET_INTERFACE`
+fn or_network_manager(
+    nl_links: Vec<WirelessLink>,
+    network_manager: impl FnOnce() -> Result<Vec<WirelessLink>>,
+) -> Result<Vec<WirelessLink>> {
+    if nl_links.is_empty() {
+        network_manager()
+    } else {
+        Ok(nl_links)
+    }
+}
+
+fn read_proc_wireless() -> Result<Vec<ProcEntry>> {
+    let contents = fs::read_to_string(PROC_WIRELESS)?;
+    // The first two lines are column headers.
+    Ok(contents
+        .lines()
+        .skip(2)
+        .filter_map(parse_proc_line)
+        .collect())
+}
+
+/// Parses a line such as ` wlan0: 0000   54.  -56.  -256  0  0  0  0  0  0`
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn parse_proc_line(row: &str) -> Option<ProcEntry> {
+    let (interface, rest) = row.split_once(':')?;
+    let mut fields = rest.split_whitespace().skip(1);
+    let link = fields.next()?.trim_end_matches('.').parse::<f64>().ok();
+    let level = fields.next()?.trim_end_matches('.').parse::<i32>().ok();
+    Some(ProcEntry {
+        interface: interface.trim().to_owned(),
+        quality: link.map(|l| (l / PROC_QUALITY_MAX * 100.0).clamp(0.0, 100.0) as u8),
+        // Drivers that do not report dBm leave a non-negative placeholder here.
+        level: level.filter(|l| *l < 0),
+    })
+}
+
+fn link_from_nl80211(nl: &mut Nl80211, entry: &ProcEntry) -> Option<WirelessLink> {
+    let ifindex = if_nametoindex(entry.interface.as_str()).ok()?;
+    let interface = nl.interface(ifindex).ok()?;
+    let station = nl.station(ifindex).unwrap_or_default();
+    Some(WirelessLink {
+        interface: entry.interface.clone(),
+        ssid: interface.ssid?,
+        signal: station.signal.or(entry.level),
+        quality: entry.quality,
+        frequency: interface.frequency,
+        bitrate: station.tx_bitrate,
+    })
+}
+
+fn links_from_network_manager() -> Result<Vec<WirelessLink>> {
+    let connection = zbus::blocking::Connection::system()?;
+    let manager = NetworkManagerProxyBlocking::new(&connection)?;
+
+    let mut links = Vec::new();
+    for path in manager.get_devices()? {
+        let device = NmDeviceProxyBlocking::builder(&connection)
+            .path(&path)?
+            .build()?;
+        if device.device_type()? != NM_DEVICE_TYPE_WIFI {
+            continue;
+        }
+        let wireless = NmWirelessProxyBlocking::builder(&connection)
+            .path(&path)?
+            .build()?;
+        let ap_path = wireless.active_access_point()?;
+        // `NetworkManager` uses "/" for "no access point".
+        if ap_path.as_str() == "/" {
+            continue;
+        }
+        let ap = NmAccessPointProxyBlocking::builder(&connection)
+            .path(ap_path)?
+            .build()?;
+
+        links.push(WirelessLink {
+            interface: device.interface()?,
+            ssid: String::from_utf8_lossy(&ap.ssid()?).into_owned(),
+            signal: None,
+            quality: ap.strength().ok(),
+            frequency: ap.frequency().ok(),
+            bitrate: wireless.bitrate().ok().filter(|v| *v > 0),
+        });
+    }
+    Ok(links)
+}
+
+fn band(frequency: u32) -> Option<&'static str> {
+    match frequency {
+        2400..=2500 => Some("2.4 GHz"),
+        4900..=5900 => Some("5 GHz"),
+        5925..=7125 => Some("6 GHz"),
+        57000..=71000 => Some("60 GHz"),
+        _ => None,
+    }
+}
+
+impl std::fmt::Display for WirelessLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.ssid)?;
+        if let Some(signal) = self.signal {
+            write!(f, ", {signal} dBm")?;
+        } else if let Some(quality) = self.quality {
+            write!(f, ", {quality}%")?;
+        }
+        if let Some(frequency) = self.frequency {
+            match band(frequency) {
+                Some(b) => write!(f, ", {b} ({frequency} MHz)")?,
+                None => write!(f, ", {frequency} MHz")?,
+            }
+        }
+        if let Some(bitrate) = self.bitrate {
+            write!(f, ", {:.1} Mb/s", f64::from(bitrate) / 1000.0)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Wireless {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = self.links.iter().map(ToString::to_string).collect();
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+impl Fetch for Wireless {
+    fn name(&self) -> &'static str {
+        "Wireless"
+    }
+
+    fn as_fetchlines(&self) -> Vec<Line> {
+        if self.links.len() == 1 {
+            vec![("Wireless", &self.links[0]).into()]
+        } else {
+            self.links
+                .iter()
+                .map(|l| (format!("Wireless {}", l.interface), l).into())
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(interface: &str) -> WirelessLink {
+        WirelessLink {
+            interface: interface.into(),
+            ssid: "HomeNet".into(),
+            signal: Some(-52),
+            quality: Some(77),
+            frequency: Some(5180),
+            bitrate: Some(866_700),
+        }
+    }
+
+    #[test]
+    fn link_display() {
+        let link = link("wlan0");
+        assert_eq!(
+            link.to_string(),
+            "HomeNet, -52 dBm, 5 GHz (5180 MHz), 866.7 Mb/s"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn network_manager_fallback() {
+        let links = or_network_manager(Vec::new(), || Ok(vec![link("wlp2s0")])).unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].interface, "wlp2s0");
+
+        let links = or_network_manager(vec![link("wlan0")], || {
+            panic!("NetworkManager queried despite nl80211 links")
+        })
+        .unwrap();
+        assert_eq!(links[0].interface, "wlan0");
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn proc_wireless_line() {
+        let entry =
+            parse_proc_line(" wlan0: 0000   54.  -56.  -256   0   0   0   0   0   0").unwrap();
+        assert_eq!(entry.interface, "wlan0");
+        assert_eq!(entry.quality, Some(77));
+        assert_eq!(entry.level, Some(-56));
+    }
+}
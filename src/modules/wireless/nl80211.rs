@@ -0,0 +1,253 @@
+//! Minimal nl80211 client over a generic netlink socket. Only the two queries
+//! the wireless module needs are implemented: interface info (SSID and
+//! frequency) and station info for the access point (signal and tx bitrate).
+
+use std::os::fd::{AsRawFd, OwnedFd};
+
+use nix::sys::socket::{
+    bind, recv, send, setsockopt, socket, sockopt::ReceiveTimeout, AddressFamily, MsgFlags,
+    NetlinkAddr, SockFlag, SockProtocol, SockType,
+};
+use nix::sys::time::TimeVal;
+
+use crate::{Error, Result};
+
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+const NLM_F_REQUEST: u16 = 0x1;
+const NLM_F_DUMP: u16 = 0x300;
+const NLMSG_HDRLEN: usize = 16;
+const GENL_HDRLEN: usize = 4;
+const NLA_HDRLEN: usize = 4;
+const NLA_TYPE_MASK: u16 = !(1 << 15 | 1 << 14);
+
+const GENL_ID_CTRL: u16 = 0x10;
+const CTRL_CMD_GETFAMILY: u8 = 3;
+const CTRL_ATTR_FAMILY_ID: u16 = 1;
+const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+
+const NL80211_CMD_GET_INTERFACE: u8 = 5;
+const NL80211_CMD_GET_STATION: u8 = 17;
+const NL80211_ATTR_IFINDEX: u16 = 3;
+const NL80211_ATTR_STA_INFO: u16 = 21;
+const NL80211_ATTR_WIPHY_FREQ: u16 = 38;
+const NL80211_ATTR_SSID: u16 = 52;
+const NL80211_STA_INFO_SIGNAL: u16 = 7;
+const NL80211_STA_INFO_TX_BITRATE: u16 = 8;
+const NL80211_RATE_INFO_BITRATE: u16 = 1;
+const NL80211_RATE_INFO_BITRATE32: u16 = 5;
+
+const RECV_BUFFER: usize = 16384;
+
+#[derive(Default)]
+pub struct InterfaceInfo {
+    pub ssid: Option<String>,
+    /// MHz
+    pub frequency: Option<u32>,
+}
+
+#[derive(Default)]
+pub struct StationInfo {
+    /// dBm
+    pub signal: Option<i32>,
+    /// kbit/s
+    pub tx_bitrate: Option<u32>,
+}
+
+pub struct Nl80211 {
+    socket: OwnedFd,
+    family: u16,
+    seq: u32,
+}
+
+impl Nl80211 {
+    /// Opens a generic netlink socket and resolves the nl80211 family id
+    ///
+    /// # Errors
+    /// Returns an error if the socket cannot be opened or nl80211 is not available
+    pub fn new() -> Result<Self> {
+        let socket = socket(
+            AddressFamily::Netlink,
+            SockType::Raw,
+            SockFlag::SOCK_CLOEXEC,
+            SockProtocol::NetlinkGeneric,
+        )?;
+        bind(socket.as_raw_fd(), &NetlinkAddr::new(0, 0))?;
+        setsockopt(&socket, ReceiveTimeout, &TimeVal::new(1, 0))?;
+
+        let mut nl = Self {
+            socket,
+            family: GENL_ID_CTRL,
+            seq: 0,
+        };
+
+        let mut attrs = Vec::new();
+        push_attr(&mut attrs, CTRL_ATTR_FAMILY_NAME, b"nl80211\0");
+        let replies = nl.request(CTRL_CMD_GETFAMILY, 0, &attrs)?;
+        nl.family = replies
+            .iter()
+            .find_map(|r| find_attr(r, CTRL_ATTR_FAMILY_ID))
+            .and_then(read_u16)
+            .ok_or(Error::Netlink)?;
+        Ok(nl)
+    }
+
+    /// Returns the SSID and frequency of the interface's current connection
+    ///
+    /// # Errors
+    /// Returns an error if the netlink request fails
+    pub fn interface(&mut self, ifindex: u32) -> Result<InterfaceInfo> {
+        let mut attrs = Vec::new();
+        push_attr(&mut attrs, NL80211_ATTR_IFINDEX, &ifindex.to_ne_bytes());
+        let replies = self.request(NL80211_CMD_GET_INTERFACE, 0, &attrs)?;
+
+        let mut info = InterfaceInfo::default();
+        for reply in &replies {
+            if let Some(ssid) = find_attr(reply, NL80211_ATTR_SSID) {
+                info.ssid = Some(String::from_utf8_lossy(ssid).into_owned());
+            }
+            if let Some(freq) = find_attr(reply, NL80211_ATTR_WIPHY_FREQ).and_then(read_u32) {
+                info.frequency = Some(freq);
+            }
+        }
+        Ok(info)
+    }
+
+    /// Returns signal and tx bitrate for the station (access point) the
+    /// interface is associated with
+    ///
+    /// # Errors
+    /// Returns an error if the netlink request fails
+    pub fn station(&mut self, ifindex: u32) -> Result<StationInfo> {
+        let mut attrs = Vec::new();
+        push_attr(&mut attrs, NL80211_ATTR_IFINDEX, &ifindex.to_ne_bytes());
+        let replies = self.request(NL80211_CMD_GET_STATION, NLM_F_DUMP, &attrs)?;
+
+        let mut info = StationInfo::default();
+        let Some(sta) = replies
+            .iter()
+            .find_map(|r| find_attr(r, NL80211_ATTR_STA_INFO))
+        else {
+            return Ok(info);
+        };
+        // The kernel stores signal as a u8 holding a signed dBm value.
+        info.signal = find_attr(sta, NL80211_STA_INFO_SIGNAL)
+            .and_then(|v| v.first())
+            .map(|v| i32::from(v.cast_signed()));
+        if let Some(rate) = find_attr(sta, NL80211_STA_INFO_TX_BITRATE) {
+            // Both attributes are in units of 100 kbit/s; BITRATE32 is present
+            // when the rate does not fit in 16 bits.
+            info.tx_bitrate = find_attr(rate, NL80211_RATE_INFO_BITRATE32)
+                .and_then(read_u32)
+                .or_else(|| {
+                    find_attr(rate, NL80211_RATE_INFO_BITRATE)
+                        .and_then(read_u16)
+                        .map(u32::from)
+                })
+                .map(|v| v * 100);
+        }
+        Ok(info)
+    }
+
+    /// Sends a generic netlink request and returns the attribute payload of
+    /// every reply message.
+    fn request(&mut self, cmd: u8, flags: u16, attrs: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.seq += 1;
+        let len = NLMSG_HDRLEN + GENL_HDRLEN + attrs.len();
+        let mut msg = Vec::with_capacity(len);
+        msg.extend_from_slice(
+            &u32::try_from(len)
+                .map_err(|_| Error::Netlink)?
+                .to_ne_bytes(),
+        );
+        msg.extend_from_slice(&self.family.to_ne_bytes());
+        msg.extend_from_slice(&(NLM_F_REQUEST | flags).to_ne_bytes());
+        msg.extend_from_slice(&self.seq.to_ne_bytes());
+        msg.extend_from_slice(&0u32.to_ne_bytes());
+        msg.extend_from_slice(&[cmd, 1, 0, 0]);
+        msg.extend_from_slice(attrs);
+        send(self.socket.as_raw_fd(), &msg, MsgFlags::empty())?;
+
+        let dump = flags & NLM_F_DUMP != 0;
+        let mut replies = Vec::new();
+        let mut buf = vec![0u8; RECV_BUFFER];
+        loop {
+            let n = recv(self.socket.as_raw_fd(), &mut buf, MsgFlags::empty())?;
+            let mut offset = 0;
+            while offset + NLMSG_HDRLEN <= n {
+                let header = &buf[offset..];
+                let msg_len = read_u32(&header[0..4]).map_or(0, |v| v as usize);
+                let msg_type = read_u16(&header[4..6]).unwrap_or(0);
+                let msg_seq = read_u32(&header[8..12]).unwrap_or(0);
+                if msg_len < NLMSG_HDRLEN || offset + msg_len > n {
+                    return Err(Error::Netlink);
+                }
+                let payload = &buf[offset + NLMSG_HDRLEN..offset + msg_len];
+                offset += align(msg_len);
+
+                if msg_seq != self.seq {
+                    continue;
+                }
+                match msg_type {
+                    NLMSG_DONE => return Ok(replies),
+                    NLMSG_ERROR => {
+                        let errno = payload
+                            .get(0..4)
+                            .and_then(read_u32)
+                            .map_or(0, u32::cast_signed);
+                        if errno == 0 {
+                            return Ok(replies);
+                        }
+                        return Err(nix::errno::Errno::from_raw(-errno).into());
+                    }
+                    _ => {
+                        if let Some(attrs) = payload.get(GENL_HDRLEN..) {
+                            replies.push(attrs.to_vec());
+                        }
+                        if !dump {
+                            return Ok(replies);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+const fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn push_attr(buf: &mut Vec<u8>, kind: u16, payload: &[u8]) {
+    let len = NLA_HDRLEN + payload.len();
+    // Attributes built here are a few bytes long, far below u16::MAX.
+    #[allow(clippy::cast_possible_truncation)]
+    buf.extend_from_slice(&(len as u16).to_ne_bytes());
+    buf.extend_from_slice(&kind.to_ne_bytes());
+    buf.extend_from_slice(payload);
+    buf.resize(buf.len() + align(len) - len, 0);
+}
+
+/// Returns the payload of the first attribute of `kind` in a flat attribute list
+fn find_attr(mut attrs: &[u8], kind: u16) -> Option<&[u8]> {
+    while attrs.len() >= NLA_HDRLEN {
+        let len = usize::from(read_u16(&attrs[0..2])?);
+        let attr_kind = read_u16(&attrs[2..4])? & NLA_TYPE_MASK;
+        if len < NLA_HDRLEN || len > attrs.len() {
+            return None;
+        }
+        if attr_kind == kind {
+            return Some(&attrs[NLA_HDRLEN..len]);
+        }
+        attrs = attrs.get(align(len)..).unwrap_or_default();
+    }
+    None
+}
+
+fn read_u16(v: &[u8]) -> Option<u16> {
+    Some(u16::from_ne_bytes(v.get(0..2)?.try_into().ok()?))
+}
+
+fn read_u32(v: &[u8]) -> Option<u32> {
+    Some(u32::from_ne_bytes(v.get(0..4)?.try_into().ok()?))
+}